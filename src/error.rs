@@ -306,6 +306,118 @@ pub enum MetadataError {
     // In the legacy system the reservation needs to be of size one for cpu limit reasons
     #[error("In the legacy system the reservation needs to be of size one for cpu limit reasons")]
     ReservationArrayShouldBeSizeOne,
+
+    /// Config's key must match seed of ['metadata', program id, 'config'] provided
+    #[error("Config's key must match seed of ['metadata', program id, 'config'] provided")]
+    InvalidConfigKey,
+
+    /// Account layout version is newer than this program understands
+    #[error("Account layout version is newer than this program understands")]
+    UnsupportedDataVersion,
+
+    /// Account needs to be signer of this transaction
+    #[error("Account needs to be signer of this transaction")]
+    AccountIsNotSigner,
+
+    /// Token transfer failed
+    #[error("Token transfer failed")]
+    TokenTransferFailed,
+
+    /// This NFT is not listed for sale
+    #[error("This NFT is not listed for sale")]
+    NFTNotListed,
+
+    /// This NFT is already listed for sale
+    #[error("This NFT is already listed for sale")]
+    NFTAlreadyListed,
+
+    /// Listed price is above the maximum the buyer agreed to pay
+    #[error("Listed price is above the maximum the buyer agreed to pay")]
+    PriceAboveMaximum,
+
+    /// The owner is not allowed to edit the name or uri of this NFT
+    #[error("The owner is not allowed to edit the name or uri of this NFT")]
+    OwnerEditsNotAllowed,
+
+    /// NFT does not belong to this collection
+    #[error("NFT does not belong to this collection")]
+    CollectionMismatch,
+
+    /// Collection authority does not match
+    #[error("Collection authority does not match")]
+    InvalidCollectionAuthority,
+
+    /// NFT mint decimals should be zero
+    #[error("NFT mint decimals should be zero")]
+    NFTMintDecimalsShouldBeZero,
+
+    /// NFT mint supply must be exactly one
+    #[error("NFT mint supply must be exactly one")]
+    NFTMintSupplyMustBeOne,
+
+    /// Token account is frozen
+    #[error("Token account is frozen")]
+    TokenAccountFrozen,
+
+    /// Bid amount does not match the amount expected
+    #[error("Bid amount does not match the amount expected")]
+    BidAmountMismatch,
+
+    /// This NFT is being auctioned
+    #[error("This NFT is being auctioned")]
+    NFTInAuction,
+
+    /// Auction must end after it starts and in the future
+    #[error("Auction must end after it starts and in the future")]
+    InvalidAuctionTimes,
+
+    /// Auction has not started yet
+    #[error("Auction has not started yet")]
+    AuctionNotStarted,
+
+    /// Auction has already ended
+    #[error("Auction has already ended")]
+    AuctionEnded,
+
+    /// Auction has not ended yet
+    #[error("Auction has not ended yet")]
+    AuctionNotEnded,
+
+    /// Bid is below the reserve price
+    #[error("Bid is below the reserve price")]
+    BidBelowReserve,
+
+    /// Bid does not beat the top bid by the minimum increment
+    #[error("Bid does not beat the top bid by the minimum increment")]
+    BidIncrementTooLow,
+
+    /// Account is not the top bidder of the auction
+    #[error("Account is not the top bidder of the auction")]
+    TopBidderMismatch,
+
+    /// Declining-price listing must start at a positive time, end after it starts and floor below its start price
+    #[error("Declining-price listing must start at a positive time, end after it starts and floor below its start price")]
+    InvalidDutchSchedule,
+
+    /// Listing has expired
+    #[error("Listing has expired")]
+    ListingExpired,
+
+    /// Listing has not expired
+    #[error("Listing has not expired")]
+    ListingNotExpired,
+
+    /// Expiry must be in the future
+    #[error("Expiry must be in the future")]
+    InvalidExpiry,
+
+    /// Token account does not hold the payment mint of the listing
+    #[error("Token account does not hold the payment mint of the listing")]
+    PaymentMintMismatch,
+
+    /// Metadata key is not the next NFT id, another create took it first, derive it again and retry
+    #[error("Metadata key is not the next NFT id, another create took it first, derive it again and retry")]
+    StaleNFTId,
 }
 
 impl PrintProgramError for MetadataError {
@@ -1,9 +1,8 @@
 use {
     crate::{
         // deprecated_instruction::{MintPrintingTokensViaTokenArgs, SetReservationListArgs},
-        state::{ NFTData,
-            // Creator, 
-            // EDITION, EDITION_MARKER_BIT_SIZE, PREFIX
+        state::{ Config, Creator, Key, NFTData, SaleStatus, AUCTION, BID, COLLECTION, CONFIG, ESCROW, MAX_LEGACY_NFT_ID, NFT_DATA_VERSION, PREFIX,
+            // EDITION, EDITION_MARKER_BIT_SIZE,
         },
     },
     borsh::{BorshDeserialize, BorshSerialize},
@@ -19,102 +18,485 @@ use {
 /// Args for create call
 pub struct CreateMetadataAccountArgs {
     /// Note that unique metadatas are disabled for now.
+    /// The id is ignored, the program assigns the next one from the config counter.
     pub data: NFTData,
     /// Whether you want your metadata to be updateable in the future.
-    // pub is_mutable: bool,
-    pub id: u8,
+    pub is_mutable: bool,
+    /// Have the program create the mint and mint its only token to the payer.
+    pub mint_token: bool,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
 pub struct UpdateNFTPriceArgs {
     /// Update price of NFT from Id for it's owner.
-    pub id: u8,
+    pub id: u64,
     pub price: u64,
+    /// Unix timestamp the listing expires at, never when None. Only for listed NFTs.
+    pub expires_at: Option<i64>,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
 pub struct PurchaseNFTArgs {
-    pub id: u8,
-    pub new_name: Option<String>,
-    pub new_uri: Option<String>,
+    pub id: u64,
     pub new_price: Option<u64>,
+    /// Most the buyer is willing to pay, guards against the price changing before the purchase lands.
+    pub max_price: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ListNFTArgs {
+    pub id: u64,
+    pub price: u64,
+    /// SPL mint the price is in, lamports when None
+    pub payment_mint: Option<Pubkey>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ListNFTDutchArgs {
+    pub id: u64,
+    /// Price at the start time, stored as the listed price
+    pub start_price: u64,
+    pub floor_price: u64,
+    /// Unix timestamp the price starts declining
+    pub start_time: i64,
+    /// Unix timestamp the price reaches the floor and stays there
+    pub end_time: i64,
+    /// SPL mint the prices are in, lamports when None
+    pub payment_mint: Option<Pubkey>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct DelistNFTArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MigrateNFTIdArgs {
+    /// Single byte id of the legacy NFT account.
+    pub id: u8,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SignMetadataArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct RemoveCreatorVerificationArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct UpdateMetadataArgs {
+    pub id: u64,
+    pub name: Option<String>,
+    pub uri: Option<String>,
+    pub creators: Option<Vec<Creator>>,
+    pub update_authority: Option<Pubkey>,
+    pub allow_owner_edits: Option<bool>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct OwnerUpdateMetadataArgs {
+    pub id: u64,
+    pub name: Option<String>,
+    pub uri: Option<String>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CreateCollectionArgs {
+    pub name: String,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct VerifyCollectionArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct UnverifyCollectionArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CloseNFTArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct BurnNFTArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct PlaceBidArgs {
+    pub id: u64,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CancelBidArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct AcceptBidArgs {
+    pub id: u64,
+    /// Amount the owner agreed to, guards against the bid changing before the accept lands.
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct StartAuctionArgs {
+    pub id: u64,
+    /// Unix timestamp from which bids are accepted
+    pub start_time: i64,
+    /// Unix timestamp after which the auction can be settled
+    pub end_time: i64,
+    pub reserve_price: u64,
+    pub min_increment: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct PlaceAuctionBidArgs {
+    pub id: u64,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct SettleAuctionArgs {
+    pub id: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ClearExpiredListingArgs {
+    pub id: u64,
 }
 
 /// Instructions supported by the Metadata program.
+// CreateMetadataAccount carries a whole NFTData, instructions are decoded once per call so boxing it buys nothing
+#[allow(clippy::large_enum_variant)]
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum MetadataInstruction {
-    /// Create Metadata object.
-    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, mint id])
-    ///   1. `[]` Mint of token asset
-    ///   2. `[signer]` Mint authority
-    ///   3. `[signer]` payer
-    ///   4. `[]` update authority info
-    ///   5. `[]` System program
-    ///   6. `[]` Rent info
+    /// Create Metadata object, the id is the next one allocated by the config counter.
+    /// Fails with StaleNFTId when another create took that id first, see `find_next_metadata_account`,
+    /// and with AlreadyInitialized when the account was already created for this mint.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, next id])
+    ///   1. `[writable]` Config key (pda of ['metadata', program id, 'config']), created on first use
+    ///   2. `[]` Mint of the NFT, decimals 0 and supply 1, `[writable, signer]` new account when minting the token
+    ///   3. `[signer]` Mint authority, only checked while the mint still has one
+    ///   4. `[signer]` payer
+    ///   5. `[]` update authority info
+    ///   6. `[]` System program
+    ///   7. `[]` Rent info
+    ///   8. `[writable]` Payer associated token account receiving the token, only when minting the token
+    ///   9. `[]` Token program, only when minting the token
+    ///   10. `[]` Associated token account program, only when minting the token
     CreateMetadataAccount(CreateMetadataAccountArgs),
     
     /// Update price of NFT from Id.
-    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, mint id])
-    ///   1. `[]` Mint of token asset
-    ///   2. `[signer]` Mint authority
-    ///   3. `[signer]` payer
-    ///   4. `[]` update authority info
-    ///   5. `[]` System program
-    ///   6. `[]` Rent info
+    /// A declining-price listing becomes a fixed-price one at the new price.
+    /// Stamps the listing time and replaces the expiry, a listing without expiry lives until delisted.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` NFT owner, the seller while the NFT is listed
+    ///   2. `[]` Owner token account holding the NFT, ignored while the NFT is listed
+    ///   3. `[]` Clock info
     UpdateNFTPrice(UpdateNFTPriceArgs),
 
-    /// Update price of NFT from Id.
-    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, mint id])
-    ///   1. `[]` Mint of token asset
-    ///   2. `[signer]` Mint authority
-    ///   3. `[signer]` payer
-    ///   4. `[]` update authority info
-    ///   5. `[]` System program
-    ///   6. `[]` Rent info
+    /// Purchase a listed NFT from Id, the token is released from escrow.
+    /// A listing with a payment mint is paid with token transfers between accounts of that mint,
+    /// the creators then get paid into their token accounts instead of their wallets.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` payer
+    ///   2. `[writable]` Seller, receives the price and the escrow rent
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[writable]` Payer token account receiving the NFT
+    ///   5. `[]` Token program
+    ///   6. `[]` System program
+    ///   7. `[]` Clock info
+    ///   8. `[writable]` Payer token account of the payment mint, only with a payment mint
+    ///   9. `[writable]` Seller token account of the payment mint, only with a payment mint
+    ///   10. `[writable]` (repeated) Wallets or payment mint token accounts of the creators with a share, in the order stored on the NFT
     PurchaseNFT(PurchaseNFTArgs),
+
+    /// Move the NFT token into a program owned escrow account and list it for sale.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` Seller, pays for the escrow account
+    ///   2. `[writable]` Seller token account holding the NFT
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[]` Mint of the NFT
+    ///   5. `[]` Token program
+    ///   6. `[]` System program
+    ///   7. `[]` Rent info
+    ///   8. `[]` Clock info
+    ///   9. `[]` Payment mint, only with a payment mint
+    ListNFT(ListNFTArgs),
+
+    /// Return a listed NFT token from escrow to the seller.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` Seller, receives the escrow rent
+    ///   2. `[writable]` Seller token account receiving the NFT
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[]` Token program
+    DelistNFT(DelistNFTArgs),
+
+    /// Move an NFT account keyed by a single byte id to the u64 keyed layout.
+    ///   0. `[writable]` Legacy NFT key (pda of ['metadata', program id, [id]])
+    ///   1. `[writable]` NFT key (pda of ['metadata', program id, id as u64 le bytes])
+    ///   2. `[signer]` payer, receives the legacy account rent
+    ///   3. `[]` System program
+    ///   4. `[]` Rent info
+    MigrateNFTId(MigrateNFTIdArgs),
+
+    /// Sign the NFT as one of its creators, marking that creator as verified.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` Creator
+    SignMetadata(SignMetadataArgs),
+
+    /// Remove the verification of a creator, only that creator can do it.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` Creator
+    RemoveCreatorVerification(RemoveCreatorVerificationArgs),
+
+    /// Update name, uri and creators of a mutable NFT, or hand the update authority over.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` Update authority key
+    UpdateMetadata(UpdateMetadataArgs),
+
+    /// Update name and uri as the owner, only when the NFT is mutable and allows owner edits.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` NFT owner, the seller while the NFT is listed
+    ///   2. `[]` Owner token account holding the NFT, ignored while the NFT is listed
+    OwnerUpdateMetadata(OwnerUpdateMetadataArgs),
+
+    /// Create a collection NFTs can be verified into.
+    ///   0. `[writable]` Collection key (pda of ['metadata', program id, 'collection', authority, name])
+    ///   1. `[writable, signer]` Collection authority, pays for the account
+    ///   2. `[]` System program
+    ///   3. `[]` Rent info
+    CreateCollection(CreateCollectionArgs),
+
+    /// Confirm the NFT belongs to the collection it points at.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` Collection authority
+    ///   2. `[]` Collection key
+    VerifyCollection(VerifyCollectionArgs),
+
+    /// Withdraw the confirmation of the NFT collection membership.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` Collection authority
+    ///   2. `[]` Collection key
+    UnverifyCollection(UnverifyCollectionArgs),
+
+    /// Close an NFT which is not listed and reclaim its rent, the id is never handed out again.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[signer]` NFT owner or update authority
+    ///   2. `[]` Owner token account holding the NFT, ignored when the update authority signs
+    ///   3. `[writable]` Recipient of the rent
+    CloseNFT(CloseNFTArgs),
+
+    /// Burn the NFT token, close the token account and the NFT account, rent goes to the owner.
+    /// Gives up the mint authority too when the owner still holds it.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` NFT owner
+    ///   2. `[writable]` Owner token account holding the NFT
+    ///   3. `[writable]` Mint of the NFT
+    ///   4. `[]` Token program
+    BurnNFT(BurnNFTArgs),
+
+    /// Offer lamports for an NFT, they are held in the bid account until it is cancelled or accepted.
+    ///   0. `[]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` Bidder, pays the bid and the bid account
+    ///   2. `[writable]` Bid key (pda of ['metadata', program id, id, 'bid', bidder])
+    ///   3. `[]` System program
+    ///   4. `[]` Rent info
+    PlaceBid(PlaceBidArgs),
+
+    /// Take back an open bid, the bid account is closed to the bidder.
+    ///   0. `[writable, signer]` Bidder
+    ///   1. `[writable]` Bid key (pda of ['metadata', program id, id, 'bid', bidder])
+    CancelBid(CancelBidArgs),
+
+    /// Sell the NFT to a bidder, the bid pays the owner and creators and the token moves to the bidder.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` NFT owner, the seller while the NFT is listed
+    ///   2. `[writable]` Owner token account holding the NFT, ignored while the NFT is listed
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow']), only used while listed
+    ///   4. `[writable]` Bid key (pda of ['metadata', program id, id, 'bid', bidder])
+    ///   5. `[writable]` Bidder, receives the bid account rent
+    ///   6. `[writable]` Bidder token account receiving the NFT
+    ///   7. `[]` Token program
+    ///   8. `[writable]` (repeated) Wallets of the creators with a share, in the order stored on the NFT
+    AcceptBid(AcceptBidArgs),
+
+    /// Move the NFT token into escrow and auction it to the highest bidder.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` Seller, pays for the escrow and auction accounts
+    ///   2. `[writable]` Seller token account holding the NFT
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[writable]` Auction key (pda of ['metadata', program id, id, 'auction'])
+    ///   5. `[]` Mint of the NFT
+    ///   6. `[]` Token program
+    ///   7. `[]` System program
+    ///   8. `[]` Rent info
+    ///   9. `[]` Clock info
+    StartAuction(StartAuctionArgs),
+
+    /// Outbid the top bid of a running auction, the previous top bidder is refunded.
+    ///   0. `[writable]` Auction key (pda of ['metadata', program id, id, 'auction'])
+    ///   1. `[writable, signer]` Bidder, the lamports are held by the auction account
+    ///   2. `[writable]` Previous top bidder, any account when nobody bid yet
+    ///   3. `[]` System program
+    ///   4. `[]` Clock info
+    PlaceAuctionBid(PlaceAuctionBidArgs),
+
+    /// Close an ended auction, anyone can send it.
+    /// The top bidder gets the NFT and the seller the top bid less royalties, the seller gets the NFT back when nobody bid.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable]` Auction key (pda of ['metadata', program id, id, 'auction'])
+    ///   2. `[writable]` Seller, receives the top bid and the rent of the escrow and auction accounts
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[writable]` Token account of the top bidder, or of the seller when nobody bid
+    ///   5. `[]` Token program
+    ///   6. `[]` Clock info
+    ///   7. `[writable]` (repeated) Wallets of the creators with a share, in the order stored on the NFT
+    SettleAuction(SettleAuctionArgs),
+
+    /// List the NFT like ListNFT, at a price declining from the start price to the floor price over time.
+    ///   0. `[writable]`  Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable, signer]` Seller, pays for the escrow account
+    ///   2. `[writable]` Seller token account holding the NFT
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[]` Mint of the NFT
+    ///   5. `[]` Token program
+    ///   6. `[]` System program
+    ///   7. `[]` Rent info
+    ///   8. `[]` Clock info
+    ///   9. `[]` Payment mint, only with a payment mint
+    ListNFTDutch(ListNFTDutchArgs),
+
+    /// Return the token of an expired listing from escrow to the seller, anyone can send it.
+    ///   0. `[writable]` Metadata key (pda of ['metadata', program id, id])
+    ///   1. `[writable]` Seller, receives the escrow rent
+    ///   2. `[writable]` Seller token account receiving the NFT
+    ///   3. `[writable]` Escrow token account (pda of ['metadata', program id, id, 'escrow'])
+    ///   4. `[]` Token program
+    ///   5. `[]` Clock info
+    ClearExpiredListing(ClearExpiredListingArgs),
+}
+
+/// Metadata account and id the next CreateMetadataAccount gets, from the config account if it exists yet.
+/// Concurrent creators derive the same account, every one but the first fails with
+/// `MetadataError::StaleNFTId` and has to read the config again and resend.
+pub fn find_next_metadata_account(program_id: &Pubkey, config: Option<&Config>) -> (Pubkey, u64) {
+    let id = config.map_or(MAX_LEGACY_NFT_ID, |config| config.nft_count.max(MAX_LEGACY_NFT_ID)) + 1;
+    let metadata_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()];
+    let (metadata_account, _) = Pubkey::find_program_address(metadata_seeds, program_id);
+    (metadata_account, id)
 }
 
-/// Creates an CreateMetadataAccounts instruction
+/// Creates an CreateMetadataAccounts instruction,
+/// `metadata_account` comes from `find_next_metadata_account`
 #[allow(clippy::too_many_arguments)]
 pub fn create_metadata_accounts(
     program_id: Pubkey,
     metadata_account: Pubkey,
-    // mint: Pubkey,
-    // mint_authority: Pubkey,
+    mint: Pubkey,
+    mint_authority: Pubkey,
     payer: Pubkey,
-    // update_authority: Pubkey,
-    id: u8,
+    update_authority: Pubkey,
     name: String,
-    // symbol: String,
+    symbol: String,
     uri: String,
     last_price: u64,
     listed_price: u64,
-    owner_nft_address: Pubkey,
+    seller_fee_basis_points: u16,
+    creators: Vec<Creator>,
+    is_mutable: bool,
+    allow_owner_edits: bool,
+    collection: Option<Pubkey>,
+    mint_token: bool,
 ) -> Instruction {
+    let config_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), CONFIG.as_bytes()];
+    let (config_account, _) = Pubkey::find_program_address(config_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(config_account, false),
+        if mint_token {
+            AccountMeta::new(mint, true)
+        } else {
+            AccountMeta::new_readonly(mint, false)
+        },
+        AccountMeta::new_readonly(mint_authority, true),
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(update_authority, false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    if mint_token {
+        accounts.push(AccountMeta::new(
+            spl_associated_token_account::get_associated_token_address(&payer, &mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+        accounts.push(AccountMeta::new_readonly(spl_associated_token_account::id(), false));
+    }
+
     Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(metadata_account, false),
-            // AccountMeta::new_readonly(mint, false),
-            //AccountMeta::new_readonly(mint_authority, true),
-            AccountMeta::new(payer, true),
-           // AccountMeta::new(owner_nft_address, false),
-            // AccountMeta::new_readonly(update_authority, update_authority_is_signer),
-            AccountMeta::new_readonly(solana_program::system_program::id(), false),
-            AccountMeta::new_readonly(sysvar::rent::id(), false),
-        ],
+        accounts,
         data: MetadataInstruction::CreateMetadataAccount(CreateMetadataAccountArgs {
             data: NFTData {
-                id,
+                key: Key::NFTDataV1,
+                version: NFT_DATA_VERSION,
+                id: 0,
                 name,
                 uri,
                 last_price,
                 listed_price,
-                owner_nft_address,
+                owner_nft_address: mint,
+                seller: Pubkey::default(),
+                sale_status: SaleStatus::NotForSale,
+                seller_fee_basis_points,
+                creators,
+                update_authority,
+                is_mutable,
+                allow_owner_edits,
+                symbol,
+                collection: collection.unwrap_or_default(),
+                collection_verified: false,
+                dutch_floor_price: 0,
+                dutch_start_time: 0,
+                dutch_end_time: 0,
+                listed_at: 0,
+                expires_at: 0,
+                payment_mint: Pubkey::default(),
             },
-            id,
+            is_mutable,
+            mint_token,
         })
         .try_to_vec()
         .unwrap(),
@@ -125,8 +507,9 @@ pub fn create_metadata_accounts(
 pub fn update_nft_price(
     program_id: Pubkey,
     metadata_account: Pubkey,
-    id: u8,
+    id: u64,
     new_price: u64,
+    expires_at: Option<i64>,
     owner: Pubkey,
     owner_nft_token_account: Pubkey,
 ) -> Instruction {
@@ -136,10 +519,12 @@ pub fn update_nft_price(
             AccountMeta::new(metadata_account, false),
             AccountMeta::new_readonly(owner, true),
             AccountMeta::new_readonly(owner_nft_token_account, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
         ],
         data: MetadataInstruction::UpdateNFTPrice(UpdateNFTPriceArgs {
             id,
             price: new_price,
+            expires_at,
         })
         .try_to_vec()
         .unwrap(),
@@ -147,36 +532,697 @@ pub fn update_nft_price(
 }
 
 /// purchase NFT instruction
+#[allow(clippy::too_many_arguments)]
 pub fn purchase_nft(
     program_id: Pubkey,
     metadata_account: Pubkey,
-    id: u8,
-    new_name: Option<String>,
-    new_uri: Option<String>,
+    id: u64,
     new_price: Option<u64>,
+    max_price: u64,
     payer: Pubkey,
-    nft_owner_address: Pubkey,
-    nft_token_account: Pubkey,
-    new_token_mint_address: Pubkey,
+    seller: Pubkey,
+    buyer_nft_token_account: Pubkey,
+    payment_token_accounts: Option<(Pubkey, Pubkey)>,
+    creators: Vec<Pubkey>,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(payer, true),
+        AccountMeta::new(seller, false),
+        AccountMeta::new(escrow_account, false),
+        AccountMeta::new(buyer_nft_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    // buyer and seller accounts of the payment mint, when the listing has one
+    if let Some((payer_payment_account, seller_payment_account)) = payment_token_accounts {
+        accounts.push(AccountMeta::new(payer_payment_account, false));
+        accounts.push(AccountMeta::new(seller_payment_account, false));
+    }
+    for creator in creators {
+        accounts.push(AccountMeta::new(creator, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetadataInstruction::PurchaseNFT(PurchaseNFTArgs {
+            id,
+            new_price,
+            max_price,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// list NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    price: u64,
+    payment_mint: Option<Pubkey>,
+    seller: Pubkey,
+    seller_nft_token_account: Pubkey,
+    mint: Pubkey,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(seller, true),
+        AccountMeta::new(seller_nft_token_account, false),
+        AccountMeta::new(escrow_account, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new_readonly(payment_mint, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetadataInstruction::ListNFT(ListNFTArgs {
+            id,
+            price,
+            payment_mint,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// list NFT at a declining price instruction
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft_dutch(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    start_price: u64,
+    floor_price: u64,
+    start_time: i64,
+    end_time: i64,
+    payment_mint: Option<Pubkey>,
+    seller: Pubkey,
+    seller_nft_token_account: Pubkey,
+    mint: Pubkey,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(seller, true),
+        AccountMeta::new(seller_nft_token_account, false),
+        AccountMeta::new(escrow_account, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new_readonly(payment_mint, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetadataInstruction::ListNFTDutch(ListNFTDutchArgs {
+            id,
+            start_price,
+            floor_price,
+            start_time,
+            end_time,
+            payment_mint,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// delist NFT instruction
+pub fn delist_nft(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    seller: Pubkey,
+    seller_nft_token_account: Pubkey,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(seller, true),
+            AccountMeta::new(seller_nft_token_account, false),
+            AccountMeta::new(escrow_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MetadataInstruction::DelistNFT(DelistNFTArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// migrate legacy NFT id instruction
+pub fn migrate_nft_id(
+    program_id: Pubkey,
+    legacy_metadata_account: Pubkey,
+    metadata_account: Pubkey,
+    payer: Pubkey,
+    id: u8,
 ) -> Instruction {
     Instruction {
         program_id,
         accounts: vec![
+            AccountMeta::new(legacy_metadata_account, false),
             AccountMeta::new(metadata_account, false),
             AccountMeta::new(payer, true),
-            AccountMeta::new(nft_owner_address, false),
-            AccountMeta::new_readonly(nft_token_account, false),
-            AccountMeta::new_readonly(new_token_mint_address, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
         ],
-        data: MetadataInstruction::PurchaseNFT(PurchaseNFTArgs {
+        data: MetadataInstruction::MigrateNFTId(MigrateNFTIdArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// sign metadata instruction
+pub fn sign_metadata(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    creator: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(creator, true),
+        ],
+        data: MetadataInstruction::SignMetadata(SignMetadataArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// remove creator verification instruction
+pub fn remove_creator_verification(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    creator: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(creator, true),
+        ],
+        data: MetadataInstruction::RemoveCreatorVerification(RemoveCreatorVerificationArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// update metadata instruction
+#[allow(clippy::too_many_arguments)]
+pub fn update_metadata(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    update_authority: Pubkey,
+    name: Option<String>,
+    uri: Option<String>,
+    creators: Option<Vec<Creator>>,
+    new_update_authority: Option<Pubkey>,
+    allow_owner_edits: Option<bool>,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(update_authority, true),
+        ],
+        data: MetadataInstruction::UpdateMetadata(UpdateMetadataArgs {
             id,
-            new_name,
-            new_uri,
-            new_price,
+            name,
+            uri,
+            creators,
+            update_authority: new_update_authority,
+            allow_owner_edits,
+        })
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// owner update metadata instruction
+pub fn owner_update_metadata(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    owner: Pubkey,
+    owner_nft_token_account: Pubkey,
+    name: Option<String>,
+    uri: Option<String>,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(owner, true),
+            AccountMeta::new_readonly(owner_nft_token_account, false),
+        ],
+        data: MetadataInstruction::OwnerUpdateMetadata(OwnerUpdateMetadataArgs { id, name, uri })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// create collection instruction
+pub fn create_collection(
+    program_id: Pubkey,
+    authority: Pubkey,
+    name: String,
+) -> Instruction {
+    let collection_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        COLLECTION.as_bytes(),
+        authority.as_ref(),
+        name.as_bytes(),
+    ];
+    let (collection_account, _) = Pubkey::find_program_address(collection_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(collection_account, false),
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: MetadataInstruction::CreateCollection(CreateCollectionArgs { name })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// verify collection instruction
+pub fn verify_collection(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    collection_authority: Pubkey,
+    collection_account: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(collection_authority, true),
+            AccountMeta::new_readonly(collection_account, false),
+        ],
+        data: MetadataInstruction::VerifyCollection(VerifyCollectionArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// unverify collection instruction
+pub fn unverify_collection(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    collection_authority: Pubkey,
+    collection_account: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(collection_authority, true),
+            AccountMeta::new_readonly(collection_account, false),
+        ],
+        data: MetadataInstruction::UnverifyCollection(UnverifyCollectionArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// close NFT instruction
+pub fn close_nft(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    authority: Pubkey,
+    owner_nft_token_account: Pubkey,
+    recipient: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(owner_nft_token_account, false),
+            AccountMeta::new(recipient, false),
+        ],
+        data: MetadataInstruction::CloseNFT(CloseNFTArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// burn NFT instruction
+pub fn burn_nft(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    owner: Pubkey,
+    owner_nft_token_account: Pubkey,
+    mint: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(owner, true),
+            AccountMeta::new(owner_nft_token_account, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MetadataInstruction::BurnNFT(BurnNFTArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// place bid instruction
+pub fn place_bid(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    amount: u64,
+    bidder: Pubkey,
+) -> Instruction {
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        bidder.as_ref(),
+    ];
+    let (bid_account, _) = Pubkey::find_program_address(bid_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(metadata_account, false),
+            AccountMeta::new(bidder, true),
+            AccountMeta::new(bid_account, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: MetadataInstruction::PlaceBid(PlaceBidArgs { id, amount })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// cancel bid instruction
+pub fn cancel_bid(
+    program_id: Pubkey,
+    id: u64,
+    bidder: Pubkey,
+) -> Instruction {
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        bidder.as_ref(),
+    ];
+    let (bid_account, _) = Pubkey::find_program_address(bid_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(bidder, true),
+            AccountMeta::new(bid_account, false),
+        ],
+        data: MetadataInstruction::CancelBid(CancelBidArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// accept bid instruction
+#[allow(clippy::too_many_arguments)]
+pub fn accept_bid(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    amount: u64,
+    owner: Pubkey,
+    owner_nft_token_account: Pubkey,
+    bidder: Pubkey,
+    bidder_nft_token_account: Pubkey,
+    creators: Vec<Pubkey>,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        bidder.as_ref(),
+    ];
+    let (bid_account, _) = Pubkey::find_program_address(bid_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(owner, true),
+        AccountMeta::new(owner_nft_token_account, false),
+        AccountMeta::new(escrow_account, false),
+        AccountMeta::new(bid_account, false),
+        AccountMeta::new(bidder, false),
+        AccountMeta::new(bidder_nft_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for creator in creators {
+        accounts.push(AccountMeta::new(creator, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetadataInstruction::AcceptBid(AcceptBidArgs { id, amount })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// start auction instruction
+#[allow(clippy::too_many_arguments)]
+pub fn start_auction(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    seller: Pubkey,
+    seller_nft_token_account: Pubkey,
+    mint: Pubkey,
+    start_time: i64,
+    end_time: i64,
+    reserve_price: u64,
+    min_increment: u64,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        AUCTION.as_bytes(),
+    ];
+    let (auction_account, _) = Pubkey::find_program_address(auction_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(seller, true),
+            AccountMeta::new(seller_nft_token_account, false),
+            AccountMeta::new(escrow_account, false),
+            AccountMeta::new(auction_account, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: MetadataInstruction::StartAuction(StartAuctionArgs {
+            id,
+            start_time,
+            end_time,
+            reserve_price,
+            min_increment,
         })
         .try_to_vec()
         .unwrap(),
     }
 }
+
+/// place auction bid instruction
+pub fn place_auction_bid(
+    program_id: Pubkey,
+    id: u64,
+    amount: u64,
+    bidder: Pubkey,
+    previous_top_bidder: Pubkey,
+) -> Instruction {
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        AUCTION.as_bytes(),
+    ];
+    let (auction_account, _) = Pubkey::find_program_address(auction_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(auction_account, false),
+            AccountMeta::new(bidder, true),
+            AccountMeta::new(previous_top_bidder, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: MetadataInstruction::PlaceAuctionBid(PlaceAuctionBidArgs { id, amount })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// settle auction instruction
+pub fn settle_auction(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    seller: Pubkey,
+    destination_nft_token_account: Pubkey,
+    creators: Vec<Pubkey>,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        AUCTION.as_bytes(),
+    ];
+    let (auction_account, _) = Pubkey::find_program_address(auction_seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new(metadata_account, false),
+        AccountMeta::new(auction_account, false),
+        AccountMeta::new(seller, false),
+        AccountMeta::new(escrow_account, false),
+        AccountMeta::new(destination_nft_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    for creator in creators {
+        accounts.push(AccountMeta::new(creator, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: MetadataInstruction::SettleAuction(SettleAuctionArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// clear expired listing instruction
+pub fn clear_expired_listing(
+    program_id: Pubkey,
+    metadata_account: Pubkey,
+    id: u64,
+    seller: Pubkey,
+    seller_nft_token_account: Pubkey,
+) -> Instruction {
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow_account, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(metadata_account, false),
+            AccountMeta::new(seller, false),
+            AccountMeta::new(seller_nft_token_account, false),
+            AccountMeta::new(escrow_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: MetadataInstruction::ClearExpiredListing(ClearExpiredListingArgs { id })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
@@ -3,15 +3,25 @@ use {
         error::MetadataError,
         instruction::MetadataInstruction,
         state::{
-            NFTData,
-            PREFIX,
+            Auction, Bid, Collection, Creator, Key, LegacyNFTData, NFTData, SaleStatus,
+            AUCTION, BID, COLLECTION, MAX_AUCTION_SIZE, MAX_BID_SIZE, MAX_COLLECTION_SIZE, MAX_DATA_SIZE, MAX_NAME_LENGTH,
+            NFT_DATA_VERSION, PREFIX,
         },
         utils::{
-            assert_owned_by, assert_initialized,
+            assert_derivation, assert_holds_nft, assert_nft_destination, assert_nft_owner,
+            assert_owned_by, assert_initialized, assert_signer,
+            assert_uninitialized, assert_creator_verification_unchanged, assert_data_valid,
+            close_program_account, create_or_allocate_account_raw,
+            puff_out_data_fields, spl_token_burn, spl_token_close_account, spl_token_transfer,
+            TokenBurnParams, TokenCloseAccountParams, TokenTransferParams,
             process_create_metadata_accounts_logic,
             process_purchase_nft_logic,
             CreateMetadataAccountsLogicArgs,
+            create_nft_mint,
+            CreateNFTMintArgs,
+            calculate_royalties, transfer_program_lamports,
             PurchaseNFTLogicArgs,
+            create_escrow, CreateEscrowArgs, release_from_escrow, ReleaseFromEscrowArgs,
         },
     },
     borsh::{BorshDeserialize, BorshSerialize},
@@ -19,9 +29,16 @@ use {
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
         msg,
+        program::invoke,
+        system_instruction,
+        program_option::COption,
         pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    spl_token::{
+        instruction::{set_authority, AuthorityType},
+        state::Mint,
     },
-    spl_token::state::{Account, Mint},
 };
 
 pub fn process_instruction<'a>(
@@ -37,7 +54,8 @@ pub fn process_instruction<'a>(
                 program_id,
                 accounts,
                 args.data,
-                args.id,
+                args.is_mutable,
+                args.mint_token,
             )
         }
         MetadataInstruction::UpdateNFTPrice(args) => {
@@ -47,6 +65,7 @@ pub fn process_instruction<'a>(
                 accounts,
                 args.id,
                 args.price,
+                args.expires_at,
             )
         }
         MetadataInstruction::PurchaseNFT(args) => {
@@ -55,9 +74,194 @@ pub fn process_instruction<'a>(
                 program_id,
                 accounts,
                 args.id,
-                args.new_name,
-                args.new_uri,
                 args.new_price,
+                args.max_price,
+            )
+        }
+        MetadataInstruction::ListNFT(args) => {
+            msg!("Instruction: List NFT from Id");
+            process_list_nft(
+                program_id,
+                accounts,
+                args.id,
+                args.price,
+                0,
+                0,
+                0,
+                args.payment_mint,
+            )
+        }
+        MetadataInstruction::ListNFTDutch(args) => {
+            msg!("Instruction: List NFT at a declining price from Id");
+            process_list_nft(
+                program_id,
+                accounts,
+                args.id,
+                args.start_price,
+                args.floor_price,
+                args.start_time,
+                args.end_time,
+                args.payment_mint,
+            )
+        }
+        MetadataInstruction::ClearExpiredListing(args) => {
+            msg!("Instruction: Clear Expired Listing");
+            process_clear_expired_listing(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::DelistNFT(args) => {
+            msg!("Instruction: Delist NFT from Id");
+            process_delist_nft(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::MigrateNFTId(args) => {
+            msg!("Instruction: Migrate NFT Id");
+            process_migrate_nft_id(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::SignMetadata(args) => {
+            msg!("Instruction: Sign Metadata");
+            process_sign_metadata(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::RemoveCreatorVerification(args) => {
+            msg!("Instruction: Remove Creator Verification");
+            process_remove_creator_verification(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::UpdateMetadata(args) => {
+            msg!("Instruction: Update Metadata");
+            process_update_metadata(
+                program_id,
+                accounts,
+                args.id,
+                args.name,
+                args.uri,
+                args.creators,
+                args.update_authority,
+                args.allow_owner_edits,
+            )
+        }
+        MetadataInstruction::OwnerUpdateMetadata(args) => {
+            msg!("Instruction: Owner Update Metadata");
+            process_owner_update_metadata(
+                program_id,
+                accounts,
+                args.id,
+                args.name,
+                args.uri,
+            )
+        }
+        MetadataInstruction::CreateCollection(args) => {
+            msg!("Instruction: Create Collection");
+            process_create_collection(
+                program_id,
+                accounts,
+                args.name,
+            )
+        }
+        MetadataInstruction::VerifyCollection(args) => {
+            msg!("Instruction: Verify Collection");
+            process_set_collection_verified(
+                program_id,
+                accounts,
+                args.id,
+                true,
+            )
+        }
+        MetadataInstruction::UnverifyCollection(args) => {
+            msg!("Instruction: Unverify Collection");
+            process_set_collection_verified(
+                program_id,
+                accounts,
+                args.id,
+                false,
+            )
+        }
+        MetadataInstruction::CloseNFT(args) => {
+            msg!("Instruction: Close NFT");
+            process_close_nft(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::BurnNFT(args) => {
+            msg!("Instruction: Burn NFT");
+            process_burn_nft(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::PlaceBid(args) => {
+            msg!("Instruction: Place Bid");
+            process_place_bid(
+                program_id,
+                accounts,
+                args.id,
+                args.amount,
+            )
+        }
+        MetadataInstruction::CancelBid(args) => {
+            msg!("Instruction: Cancel Bid");
+            process_cancel_bid(
+                program_id,
+                accounts,
+                args.id,
+            )
+        }
+        MetadataInstruction::AcceptBid(args) => {
+            msg!("Instruction: Accept Bid");
+            process_accept_bid(
+                program_id,
+                accounts,
+                args.id,
+                args.amount,
+            )
+        }
+        MetadataInstruction::StartAuction(args) => {
+            msg!("Instruction: Start Auction");
+            process_start_auction(
+                program_id,
+                accounts,
+                args.id,
+                args.start_time,
+                args.end_time,
+                args.reserve_price,
+                args.min_increment,
+            )
+        }
+        MetadataInstruction::PlaceAuctionBid(args) => {
+            msg!("Instruction: Place Auction Bid");
+            process_place_auction_bid(
+                program_id,
+                accounts,
+                args.id,
+                args.amount,
+            )
+        }
+        MetadataInstruction::SettleAuction(args) => {
+            msg!("Instruction: Settle Auction");
+            process_settle_auction(
+                program_id,
+                accounts,
+                args.id,
             )
         }
     }
@@ -67,24 +271,49 @@ pub fn process_create_metadata_accounts<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
     data: NFTData,
-    id: u8,
+    is_mutable: bool,
+    mint_token: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let metadata_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_authority_info = next_account_info(account_info_iter)?;
     let payer_account_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
     let system_account_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
 
+    if mint_token {
+        let payer_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        create_nft_mint(CreateNFTMintArgs {
+            mint_info,
+            mint_authority_info,
+            payer_account_info,
+            payer_token_account_info,
+            token_program_info,
+            associated_token_program_info,
+            system_account_info,
+            rent_info,
+        })?;
+    }
+
     process_create_metadata_accounts_logic(
         &program_id,
         CreateMetadataAccountsLogicArgs {
             metadata_account_info,
+            config_account_info,
+            mint_info,
+            mint_authority_info,
             payer_account_info,
+            update_authority_info,
             system_account_info,
             rent_info,
         },
         data,
-        id,
+        is_mutable,
     )
 }
 
@@ -92,19 +321,20 @@ pub fn process_create_metadata_accounts<'a>(
 pub fn process_purchase_nft<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    id: u8,
-    new_name: Option<String>,
-    new_uri: Option<String>,
+    id: u64,
     price: Option<u64>,
+    max_price: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let nftdata_account_info = next_account_info(account_info_iter)?;
     let payer_account_info = next_account_info(account_info_iter)?;
     let nft_owner_address_info = next_account_info(account_info_iter)?;
-    let nft_account_info = next_account_info(account_info_iter)?;
-    let new_token_mint_address = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let buyer_nft_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
     let system_account_info = next_account_info(account_info_iter)?;
-    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let remaining_account_infos = account_info_iter.as_slice();
 
     process_purchase_nft_logic(
         &program_id,
@@ -112,15 +342,16 @@ pub fn process_purchase_nft<'a>(
             nftdata_account_info,
             payer_account_info,
             nft_owner_address_info,
-            nft_account_info,
-            new_token_mint_address,
+            escrow_account_info,
+            buyer_nft_account_info,
+            token_program_info,
             system_account_info,
-            rent_info,
+            clock_info,
+            remaining_account_infos,
         },
         id,
-        new_name,
-        new_uri,
         price,
+        max_price,
     )
 }
 
@@ -128,17 +359,22 @@ pub fn process_purchase_nft<'a>(
 pub fn process_update_NFT_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    NFT_id: u8,
+    NFT_id: u64,
     new_price: u64,
+    expires_at: Option<i64>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let metadata_account_info = next_account_info(account_info_iter)?;
     let owner_account_info = next_account_info(account_info_iter)?;
     let owner_nft_token_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    assert_signer(owner_account_info)?;
+
     let metadata_seeds = &[
         PREFIX.as_bytes(),
         program_id.as_ref(),
-        &[NFT_id],
+        &NFT_id.to_le_bytes(),
     ];
     let (metadata_key, _) =
         Pubkey::find_program_address(metadata_seeds, program_id);
@@ -150,21 +386,1243 @@ pub fn process_update_NFT_price(
     assert_owned_by(metadata_account_info, program_id)?;
 
     let mut metadata = NFTData::from_account_info(metadata_account_info)?;
-    let token_account: Account = assert_initialized(&owner_nft_token_account_info)?;
-    msg!("--> retrived: {}, generated: {}", metadata.owner_nft_address, token_account.mint);
+    assert_nft_owner(&metadata, owner_account_info, owner_nft_token_account_info)?;
+
+    let clock = Clock::from_account_info(clock_info)?;
+    if let Some(expires_at) = expires_at {
+        // only a listing can expire
+        if !metadata.is_listed() {
+            return Err(MetadataError::NFTNotListed.into());
+        }
+        if expires_at <= clock.unix_timestamp {
+            return Err(MetadataError::InvalidExpiry.into());
+        }
+    }
+
+    metadata.listed_price = new_price;
+    metadata.dutch_floor_price = 0;
+    metadata.dutch_start_time = 0;
+    metadata.dutch_end_time = 0;
+    if metadata.is_listed() {
+        metadata.listed_at = clock.unix_timestamp;
+    }
+    metadata.expires_at = expires_at.unwrap_or(0);
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Move a single byte id NFT account to its u64 keyed address
+pub fn process_migrate_nft_id<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    legacy_id: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let legacy_metadata_account_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    assert_signer(payer_account_info)?;
+
+    let legacy_metadata_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &[legacy_id],
+    ];
+    let (legacy_metadata_key, _) =
+        Pubkey::find_program_address(legacy_metadata_seeds, program_id);
+    if *legacy_metadata_account_info.key != legacy_metadata_key {
+        return Err(MetadataError::InvalidMetadataKey.into());
+    }
+    assert_owned_by(legacy_metadata_account_info, program_id)?;
+
+    // the config counter never hands out ids up to MAX_LEGACY_NFT_ID, so this one is still free
+    let id = legacy_id as u64;
+    let metadata_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+    ];
+    let (metadata_key, metadata_bump_seed) =
+        Pubkey::find_program_address(metadata_seeds, program_id);
+    if *metadata_account_info.key != metadata_key {
+        return Err(MetadataError::InvalidMetadataKey.into());
+    }
+    assert_uninitialized(metadata_account_info)?;
+    let metadata_authority_signer_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        &[metadata_bump_seed],
+    ];
+
+    let legacy = LegacyNFTData::from_account_info(legacy_metadata_account_info)?;
+
+    create_or_allocate_account_raw(
+        *program_id,
+        metadata_account_info,
+        rent_info,
+        system_account_info,
+        payer_account_info,
+        MAX_DATA_SIZE,
+        metadata_authority_signer_seeds,
+    )?;
+
+    let mut metadata = NFTData {
+        key: Key::NFTDataV1,
+        version: NFT_DATA_VERSION,
+        id,
+        name: legacy.name,
+        uri: legacy.uri,
+        last_price: legacy.last_price,
+        listed_price: legacy.listed_price,
+        owner_nft_address: legacy.owner_nft_address,
+        seller: Pubkey::default(),
+        sale_status: SaleStatus::NotForSale,
+        seller_fee_basis_points: 0,
+        creators: vec![],
+        // legacy NFTs never had an authority, their data stays as it is
+        update_authority: Pubkey::default(),
+        is_mutable: false,
+        allow_owner_edits: false,
+        symbol: String::new(),
+        collection: Pubkey::default(),
+        collection_verified: false,
+        dutch_floor_price: 0,
+        dutch_start_time: 0,
+        dutch_end_time: 0,
+        listed_at: 0,
+        expires_at: 0,
+        payment_mint: Pubkey::default(),
+    };
+    puff_out_data_fields(&mut metadata);
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+
+    // close the legacy account, its rent goes back to the payer
+    close_program_account(legacy_metadata_account_info, payer_account_info)?;
+
+    msg!("--> NFT {} migrated", id);
+    Ok(())
+}
+
+/// Move the NFT token into its escrow account and put it up for sale
+/// A `dutch_end_time` of 0 lists at the fixed `price`, otherwise the price declines to `dutch_floor_price`
+#[allow(clippy::too_many_arguments)]
+pub fn process_list_nft<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+    price: u64,
+    dutch_floor_price: u64,
+    dutch_start_time: i64,
+    dutch_end_time: i64,
+    payment_mint: Option<Pubkey>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let seller_nft_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    assert_signer(seller_account_info)?;
+    if let Some(payment_mint) = payment_mint {
+        let payment_mint_info = next_account_info(account_info_iter)?;
+        if *payment_mint_info.key != payment_mint {
+            return Err(MetadataError::PaymentMintMismatch.into());
+        }
+        assert_owned_by(payment_mint_info, &spl_token::id())?;
+        let _: Mint = assert_initialized(payment_mint_info)?;
+    }
+    if dutch_end_time != 0
+        && (dutch_start_time <= 0 || dutch_end_time <= dutch_start_time || dutch_floor_price > price)
+    {
+        return Err(MetadataError::InvalidDutchSchedule.into());
+    }
+
+    let metadata_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+    ];
+    let (metadata_key, _) =
+        Pubkey::find_program_address(metadata_seeds, program_id);
+    if *metadata_account_info.key != metadata_key {
+        return Err(MetadataError::InvalidMetadataKey.into());
+    }
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.is_listed() {
+        return Err(MetadataError::NFTAlreadyListed.into());
+    }
+    if metadata.is_in_auction() {
+        return Err(MetadataError::NFTInAuction.into());
+    }
+    if metadata.owner_nft_address != *mint_info.key {
+        return Err(MetadataError::MintMismatch.into());
+    }
+
+    assert_holds_nft(
+        seller_nft_token_account_info,
+        &metadata.owner_nft_address,
+        seller_account_info.key,
+    )?;
+
+    create_escrow(
+        program_id,
+        CreateEscrowArgs {
+            metadata_account_info,
+            escrow_account_info,
+            mint_info,
+            payer_account_info: seller_account_info,
+            token_program_info,
+            system_account_info,
+            rent_info,
+        },
+        id,
+    )?;
+
+    msg!("--> Move NFT into escrow");
+    spl_token_transfer(TokenTransferParams {
+        source: seller_nft_token_account_info.clone(),
+        destination: escrow_account_info.clone(),
+        amount: 1,
+        authority: seller_account_info.clone(),
+        authority_signer_seeds: None,
+        token_program: token_program_info.clone(),
+    })?;
+
+    metadata.seller = *seller_account_info.key;
+    metadata.sale_status = SaleStatus::Listed;
+    metadata.listed_price = price;
+    metadata.dutch_floor_price = dutch_floor_price;
+    metadata.dutch_start_time = dutch_start_time;
+    metadata.dutch_end_time = dutch_end_time;
+    metadata.payment_mint = payment_mint.unwrap_or_default();
+    metadata.listed_at = Clock::from_account_info(clock_info)?.unix_timestamp;
+    // a new listing starts without expiry, see UpdateNFTPrice
+    metadata.expires_at = 0;
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Return a listed NFT token from escrow to the seller
+pub fn process_delist_nft<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let seller_nft_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(seller_account_info)?;
+
+    let metadata_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+    ];
+    let (metadata_key, _) =
+        Pubkey::find_program_address(metadata_seeds, program_id);
+    if *metadata_account_info.key != metadata_key {
+        return Err(MetadataError::InvalidMetadataKey.into());
+    }
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
 
-    assert_owned_by(owner_nft_token_account_info, &spl_token::id())?;
-    if metadata.owner_nft_address !=  token_account.mint {
-        return Err(MetadataError::OwnerMismatch.into());
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if !metadata.is_listed() {
+        return Err(MetadataError::NFTNotListed.into());
     }
-    msg!("---> NFT Onwer address: {}, Retrieved: {}", owner_account_info.key, token_account.owner);
-    let token_account: Account = assert_initialized(&owner_nft_token_account_info)?;
-    if token_account.owner != *owner_account_info.key {
+    if metadata.seller != *seller_account_info.key {
         return Err(MetadataError::InvalidOwner.into());
     }
+    assert_nft_destination(
+        seller_nft_token_account_info,
+        &metadata.owner_nft_address,
+        seller_account_info.key,
+    )?;
 
-    metadata.listed_price = new_price;
+    msg!("--> Return NFT from escrow");
+    release_from_escrow(
+        program_id,
+        ReleaseFromEscrowArgs {
+            metadata_account_info,
+            escrow_account_info,
+            destination_account_info: seller_nft_token_account_info,
+            rent_destination_info: seller_account_info,
+            token_program_info,
+        },
+        id,
+    )?;
+
+    metadata.clear_listing();
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Verify the signing creator on the NFT
+pub fn process_sign_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let creator_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(creator_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    let creator = metadata
+        .creators
+        .iter_mut()
+        .find(|creator| creator.address == *creator_account_info.key)
+        .ok_or(MetadataError::CreatorNotFound)?;
+    creator.verified = true;
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Remove the verification of the signing creator on the NFT
+pub fn process_remove_creator_verification(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let creator_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(creator_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    let creator = metadata
+        .creators
+        .iter_mut()
+        .find(|creator| creator.address == *creator_account_info.key)
+        .ok_or(MetadataError::CreatorNotFound)?;
+    creator.verified = false;
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Update name, uri and creators of a mutable NFT under its update authority
+#[allow(clippy::too_many_arguments)]
+pub fn process_update_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+    name: Option<String>,
+    uri: Option<String>,
+    creators: Option<Vec<Creator>>,
+    update_authority: Option<Pubkey>,
+    allow_owner_edits: Option<bool>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.update_authority != *update_authority_info.key {
+        return Err(MetadataError::UpdateAuthorityIncorrect.into());
+    }
+    if !update_authority_info.is_signer {
+        return Err(MetadataError::UpdateAuthorityIsNotSigner.into());
+    }
+
+    if name.is_some() || uri.is_some() || creators.is_some() || allow_owner_edits.is_some() {
+        if !metadata.is_mutable {
+            return Err(MetadataError::DataIsImmutable.into());
+        }
+
+        let mut data = metadata.clone();
+        // drop the empty slots left by puffing, they are added back before saving
+        data.creators.retain(|creator| creator.address != Pubkey::default());
+        if let Some(name) = name {
+            data.name = name;
+        }
+        if let Some(uri) = uri {
+            data.uri = uri;
+        }
+        if let Some(creators) = creators {
+            assert_creator_verification_unchanged(
+                &creators,
+                &data.creators,
+                update_authority_info.key,
+            )?;
+            data.creators = creators;
+        }
+        if let Some(allow_owner_edits) = allow_owner_edits {
+            data.allow_owner_edits = allow_owner_edits;
+        }
+        assert_data_valid(&data, &metadata)?;
+        metadata = data;
+    }
+
+    if let Some(update_authority) = update_authority {
+        metadata.update_authority = update_authority;
+    }
+
+    puff_out_data_fields(&mut metadata);
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Update name and uri as the owner of a mutable NFT which allows owner edits
+pub fn process_owner_update_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+    name: Option<String>,
+    uri: Option<String>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let owner_account_info = next_account_info(account_info_iter)?;
+    let owner_nft_token_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(owner_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if !metadata.is_mutable {
+        return Err(MetadataError::DataIsImmutable.into());
+    }
+    if !metadata.allow_owner_edits {
+        return Err(MetadataError::OwnerEditsNotAllowed.into());
+    }
+    assert_nft_owner(&metadata, owner_account_info, owner_nft_token_account_info)?;
+
+    let mut data = metadata.clone();
+    data.creators.retain(|creator| creator.address != Pubkey::default());
+    if let Some(name) = name {
+        data.name = name;
+    }
+    if let Some(uri) = uri {
+        data.uri = uri;
+    }
+    assert_data_valid(&data, &metadata)?;
+    metadata = data;
+
+    puff_out_data_fields(&mut metadata);
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Create a collection account owned by the signing authority
+pub fn process_create_collection<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let collection_account_info = next_account_info(account_info_iter)?;
+    let authority_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_account_info)?;
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(MetadataError::NameTooLong.into());
+    }
+
+    let collection_bump_seed = assert_derivation(
+        program_id,
+        collection_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            COLLECTION.as_bytes(),
+            authority_account_info.key.as_ref(),
+            name.as_bytes(),
+        ],
+    )?;
+    assert_uninitialized(collection_account_info)?;
+    create_or_allocate_account_raw(
+        *program_id,
+        collection_account_info,
+        rent_info,
+        system_account_info,
+        authority_account_info,
+        MAX_COLLECTION_SIZE,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            COLLECTION.as_bytes(),
+            authority_account_info.key.as_ref(),
+            name.as_bytes(),
+            &[collection_bump_seed],
+        ],
+    )?;
+
+    let mut array_of_zeroes = vec![];
+    while array_of_zeroes.len() < MAX_NAME_LENGTH - name.len() {
+        array_of_zeroes.push(0u8);
+    }
+    let collection = Collection {
+        key: Key::CollectionV1,
+        authority: *authority_account_info.key,
+        name: name + std::str::from_utf8(&array_of_zeroes).unwrap(),
+    };
+    collection.serialize(&mut &mut collection_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Set or clear the collection verified flag of an NFT as the collection authority
+pub fn process_set_collection_verified(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+    verified: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let authority_account_info = next_account_info(account_info_iter)?;
+    let collection_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    assert_owned_by(collection_account_info, program_id)?;
+
+    let collection = Collection::from_account_info(collection_account_info)?;
+    if collection.authority != *authority_account_info.key {
+        return Err(MetadataError::InvalidCollectionAuthority.into());
+    }
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.collection != *collection_account_info.key {
+        return Err(MetadataError::CollectionMismatch.into());
+    }
+    metadata.collection_verified = verified;
+
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Close an NFT account as its owner or update authority and hand the rent to the recipient.
+/// The config counter never hands out an id twice, so the id stays retired once closed.
+pub fn process_close_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let authority_account_info = next_account_info(account_info_iter)?;
+    let owner_nft_token_account_info = next_account_info(account_info_iter)?;
+    let recipient_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+
+    let metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.is_listed() {
+        // the token would be stuck in escrow without the account signing for it
+        return Err(MetadataError::NFTAlreadyListed.into());
+    }
+    if metadata.is_in_auction() {
+        return Err(MetadataError::NFTInAuction.into());
+    }
+    if metadata.update_authority != *authority_account_info.key {
+        assert_holds_nft(
+            owner_nft_token_account_info,
+            &metadata.owner_nft_address,
+            authority_account_info.key,
+        )?;
+    }
+
+    close_program_account(metadata_account_info, recipient_account_info)?;
+
+    msg!("--> NFT {} closed", id);
+    Ok(())
+}
+
+/// Burn the NFT token and close its account, the rent of both goes back to the owner
+pub fn process_burn_nft<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let owner_account_info = next_account_info(account_info_iter)?;
+    let owner_nft_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_signer(owner_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+
+    let metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.is_listed() {
+        return Err(MetadataError::NFTAlreadyListed.into());
+    }
+    if metadata.is_in_auction() {
+        return Err(MetadataError::NFTInAuction.into());
+    }
+    if metadata.owner_nft_address != *mint_info.key {
+        return Err(MetadataError::MintMismatch.into());
+    }
+
+    assert_holds_nft(
+        owner_nft_token_account_info,
+        &metadata.owner_nft_address,
+        owner_account_info.key,
+    )?;
+    let mint: Mint = assert_initialized(mint_info)?;
+
+    msg!("--> Burn the NFT token");
+    spl_token_burn(TokenBurnParams {
+        mint: mint_info.clone(),
+        source: owner_nft_token_account_info.clone(),
+        amount: 1,
+        authority: owner_account_info.clone(),
+        authority_signer_seeds: None,
+        token_program: token_program_info.clone(),
+    })?;
+    spl_token_close_account(TokenCloseAccountParams {
+        account: owner_nft_token_account_info.clone(),
+        destination: owner_account_info.clone(),
+        authority: owner_account_info.clone(),
+        authority_signer_seeds: None,
+        token_program: token_program_info.clone(),
+    })?;
+
+    // nobody can mint the NFT again once the owner gives up the mint authority
+    if mint.mint_authority == COption::Some(*owner_account_info.key) {
+        invoke(
+            &set_authority(
+                token_program_info.key,
+                mint_info.key,
+                None,
+                AuthorityType::MintTokens,
+                owner_account_info.key,
+                &[],
+            )?,
+            &[
+                mint_info.clone(),
+                owner_account_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
+
+    close_program_account(metadata_account_info, owner_account_info)?;
+
+    msg!("--> NFT {} burned", id);
+    Ok(())
+}
+
+/// Open a bid on an NFT, the offered lamports are held by the bid account
+pub fn process_place_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let bidder_account_info = next_account_info(account_info_iter)?;
+    let bid_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    assert_signer(bidder_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    NFTData::from_account_info(metadata_account_info)?;
+
+    let bid_bump_seed = assert_derivation(
+        program_id,
+        bid_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            BID.as_bytes(),
+            bidder_account_info.key.as_ref(),
+        ],
+    )?;
+    assert_uninitialized(bid_account_info)?;
+    create_or_allocate_account_raw(
+        *program_id,
+        bid_account_info,
+        rent_info,
+        system_account_info,
+        bidder_account_info,
+        MAX_BID_SIZE,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            BID.as_bytes(),
+            bidder_account_info.key.as_ref(),
+            &[bid_bump_seed],
+        ],
+    )?;
+
+    msg!("--> Escrow {} lamports in the bid", amount);
+    invoke(
+        &system_instruction::transfer(bidder_account_info.key, bid_account_info.key, amount),
+        &[
+            bidder_account_info.clone(),
+            bid_account_info.clone(),
+            system_account_info.clone(),
+        ],
+    )?;
+
+    let bid = Bid {
+        key: Key::BidV1,
+        id,
+        bidder: *bidder_account_info.key,
+        amount,
+    };
+    bid.serialize(&mut &mut bid_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Close an open bid, the bidder gets the offered lamports and the rent back
+pub fn process_cancel_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let bidder_account_info = next_account_info(account_info_iter)?;
+    let bid_account_info = next_account_info(account_info_iter)?;
+
+    assert_signer(bidder_account_info)?;
+    assert_derivation(
+        program_id,
+        bid_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            BID.as_bytes(),
+            bidder_account_info.key.as_ref(),
+        ],
+    )?;
+    assert_owned_by(bid_account_info, program_id)?;
+    Bid::from_account_info(bid_account_info)?;
+
+    close_program_account(bid_account_info, bidder_account_info)?;
+    Ok(())
+}
+
+/// Sell the NFT to a bidder, paying the owner and creators out of the bid account
+pub fn process_accept_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let owner_account_info = next_account_info(account_info_iter)?;
+    let owner_nft_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let bid_account_info = next_account_info(account_info_iter)?;
+    let bidder_account_info = next_account_info(account_info_iter)?;
+    let bidder_nft_token_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let creator_account_infos = account_info_iter.as_slice();
+
+    assert_signer(owner_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+
+    assert_derivation(
+        program_id,
+        bid_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            BID.as_bytes(),
+            bidder_account_info.key.as_ref(),
+        ],
+    )?;
+    assert_owned_by(bid_account_info, program_id)?;
+    let bid = Bid::from_account_info(bid_account_info)?;
+    if bid.amount != amount {
+        return Err(MetadataError::BidAmountMismatch.into());
+    }
+
+    assert_nft_destination(
+        bidder_nft_token_account_info,
+        &metadata.owner_nft_address,
+        bidder_account_info.key,
+    )?;
+
+    assert_nft_owner(&metadata, owner_account_info, owner_nft_token_account_info)?;
+    if metadata.is_listed() {
+        release_from_escrow(
+            program_id,
+            ReleaseFromEscrowArgs {
+                metadata_account_info,
+                escrow_account_info,
+                destination_account_info: bidder_nft_token_account_info,
+                rent_destination_info: owner_account_info,
+                token_program_info,
+            },
+            id,
+        )?;
+        metadata.clear_listing();
+    } else {
+        spl_token_transfer(TokenTransferParams {
+            source: owner_nft_token_account_info.clone(),
+            destination: bidder_nft_token_account_info.clone(),
+            amount: 1,
+            authority: owner_account_info.clone(),
+            authority_signer_seeds: None,
+            token_program: token_program_info.clone(),
+        })?;
+    }
+
+    let (payouts, owner_amount) = calculate_royalties(&metadata, bid.amount)?;
+    if creator_account_infos.len() != payouts.len() {
+        return Err(MetadataError::CreatorNotFound.into());
+    }
+    for ((address, amount), creator_account_info) in payouts.iter().zip(creator_account_infos) {
+        if creator_account_info.key != address {
+            return Err(MetadataError::CreatorNotFound.into());
+        }
+        transfer_program_lamports(bid_account_info, creator_account_info, *amount)?;
+    }
+    msg!("--> Pay {} lamports of the bid to the owner", owner_amount);
+    transfer_program_lamports(bid_account_info, owner_account_info, owner_amount)?;
+    close_program_account(bid_account_info, bidder_account_info)?;
+
+    metadata.last_price = bid.amount;
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Move the NFT token into escrow and open an auction for it
+#[allow(clippy::too_many_arguments)]
+pub fn process_start_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+    start_time: i64,
+    end_time: i64,
+    reserve_price: u64,
+    min_increment: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let seller_nft_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let auction_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    assert_signer(seller_account_info)?;
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+
+    let clock = Clock::from_account_info(clock_info)?;
+    if end_time <= start_time || end_time <= clock.unix_timestamp {
+        return Err(MetadataError::InvalidAuctionTimes.into());
+    }
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if metadata.is_listed() {
+        return Err(MetadataError::NFTAlreadyListed.into());
+    }
+    if metadata.is_in_auction() {
+        return Err(MetadataError::NFTInAuction.into());
+    }
+    if metadata.owner_nft_address != *mint_info.key {
+        return Err(MetadataError::MintMismatch.into());
+    }
+
+    assert_holds_nft(
+        seller_nft_token_account_info,
+        &metadata.owner_nft_address,
+        seller_account_info.key,
+    )?;
+
+    let auction_bump_seed = assert_derivation(
+        program_id,
+        auction_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            AUCTION.as_bytes(),
+        ],
+    )?;
+    assert_uninitialized(auction_account_info)?;
+
+    create_escrow(
+        program_id,
+        CreateEscrowArgs {
+            metadata_account_info,
+            escrow_account_info,
+            mint_info,
+            payer_account_info: seller_account_info,
+            token_program_info,
+            system_account_info,
+            rent_info,
+        },
+        id,
+    )?;
+    create_or_allocate_account_raw(
+        *program_id,
+        auction_account_info,
+        rent_info,
+        system_account_info,
+        seller_account_info,
+        MAX_AUCTION_SIZE,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            AUCTION.as_bytes(),
+            &[auction_bump_seed],
+        ],
+    )?;
+
+    msg!("--> Move NFT into escrow");
+    spl_token_transfer(TokenTransferParams {
+        source: seller_nft_token_account_info.clone(),
+        destination: escrow_account_info.clone(),
+        amount: 1,
+        authority: seller_account_info.clone(),
+        authority_signer_seeds: None,
+        token_program: token_program_info.clone(),
+    })?;
+
+    let auction = Auction {
+        key: Key::AuctionV1,
+        id,
+        seller: *seller_account_info.key,
+        start_time,
+        end_time,
+        reserve_price,
+        min_increment,
+        top_bid: 0,
+        top_bidder: Pubkey::default(),
+    };
+    auction.serialize(&mut &mut auction_account_info.data.borrow_mut()[..])?;
+
+    metadata.seller = *seller_account_info.key;
+    metadata.sale_status = SaleStatus::InAuction;
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Take the top bid of a running auction, refunding the previous top bidder
+pub fn process_place_auction_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let auction_account_info = next_account_info(account_info_iter)?;
+    let bidder_account_info = next_account_info(account_info_iter)?;
+    let previous_bidder_account_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    assert_signer(bidder_account_info)?;
+    assert_derivation(
+        program_id,
+        auction_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            AUCTION.as_bytes(),
+        ],
+    )?;
+    assert_owned_by(auction_account_info, program_id)?;
+    let mut auction = Auction::from_account_info(auction_account_info)?;
+
+    let clock = Clock::from_account_info(clock_info)?;
+    if clock.unix_timestamp < auction.start_time {
+        return Err(MetadataError::AuctionNotStarted.into());
+    }
+    if clock.unix_timestamp >= auction.end_time {
+        return Err(MetadataError::AuctionEnded.into());
+    }
+
+    if auction.has_bids() {
+        let minimum = auction
+            .top_bid
+            .checked_add(auction.min_increment)
+            .ok_or(MetadataError::NumericalOverflowError)?;
+        if amount <= auction.top_bid || amount < minimum {
+            return Err(MetadataError::BidIncrementTooLow.into());
+        }
+        if *previous_bidder_account_info.key != auction.top_bidder {
+            return Err(MetadataError::TopBidderMismatch.into());
+        }
+    } else if amount < auction.reserve_price {
+        return Err(MetadataError::BidBelowReserve.into());
+    }
+
+    msg!("--> Escrow {} lamports in the auction", amount);
+    invoke(
+        &system_instruction::transfer(bidder_account_info.key, auction_account_info.key, amount),
+        &[
+            bidder_account_info.clone(),
+            auction_account_info.clone(),
+            system_account_info.clone(),
+        ],
+    )?;
+    if auction.has_bids() {
+        msg!("--> Refund {} lamports to {}", auction.top_bid, auction.top_bidder);
+        transfer_program_lamports(auction_account_info, previous_bidder_account_info, auction.top_bid)?;
+    }
+
+    auction.top_bid = amount;
+    auction.top_bidder = *bidder_account_info.key;
+    auction.serialize(&mut &mut auction_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Close an ended auction, paying the seller and creators and releasing the NFT to the top bidder
+pub fn process_settle_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let auction_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let destination_nft_token_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let creator_account_infos = account_info_iter.as_slice();
+
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+
+    assert_derivation(
+        program_id,
+        auction_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            AUCTION.as_bytes(),
+        ],
+    )?;
+    assert_owned_by(auction_account_info, program_id)?;
+    let auction = Auction::from_account_info(auction_account_info)?;
+    if *seller_account_info.key != auction.seller {
+        return Err(MetadataError::InvalidOwner.into());
+    }
+
+    let clock = Clock::from_account_info(clock_info)?;
+    if clock.unix_timestamp < auction.end_time {
+        return Err(MetadataError::AuctionNotEnded.into());
+    }
+
+    // the NFT goes back to the seller when nobody bid
+    let recipient = if auction.has_bids() {
+        auction.top_bidder
+    } else {
+        auction.seller
+    };
+    assert_nft_destination(
+        destination_nft_token_account_info,
+        &metadata.owner_nft_address,
+        &recipient,
+    )?;
+
+    msg!("--> Release NFT from escrow to {}", recipient);
+    release_from_escrow(
+        program_id,
+        ReleaseFromEscrowArgs {
+            metadata_account_info,
+            escrow_account_info,
+            destination_account_info: destination_nft_token_account_info,
+            rent_destination_info: seller_account_info,
+            token_program_info,
+        },
+        id,
+    )?;
+
+    if auction.has_bids() {
+        let (payouts, seller_amount) = calculate_royalties(&metadata, auction.top_bid)?;
+        if creator_account_infos.len() != payouts.len() {
+            return Err(MetadataError::CreatorNotFound.into());
+        }
+        for ((address, amount), creator_account_info) in payouts.iter().zip(creator_account_infos) {
+            if creator_account_info.key != address {
+                return Err(MetadataError::CreatorNotFound.into());
+            }
+            transfer_program_lamports(auction_account_info, creator_account_info, *amount)?;
+        }
+        msg!("--> Pay {} lamports of the top bid to the seller", seller_amount);
+        transfer_program_lamports(auction_account_info, seller_account_info, seller_amount)?;
+        metadata.last_price = auction.top_bid;
+    }
+    close_program_account(auction_account_info, seller_account_info)?;
+
+    metadata.clear_listing();
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Return the token of an expired listing from escrow to the seller
+pub fn process_clear_expired_listing<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let seller_nft_token_account_info = next_account_info(account_info_iter)?;
+    let escrow_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_owned_by(metadata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+
+    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    if !metadata.is_listed() {
+        return Err(MetadataError::NFTNotListed.into());
+    }
+    let clock = Clock::from_account_info(clock_info)?;
+    if !metadata.is_expired(clock.unix_timestamp) {
+        return Err(MetadataError::ListingNotExpired.into());
+    }
+    if metadata.seller != *seller_account_info.key {
+        return Err(MetadataError::InvalidOwner.into());
+    }
+    assert_nft_destination(
+        seller_nft_token_account_info,
+        &metadata.owner_nft_address,
+        seller_account_info.key,
+    )?;
+
+    msg!("--> Return NFT of the expired listing from escrow");
+    release_from_escrow(
+        program_id,
+        ReleaseFromEscrowArgs {
+            metadata_account_info,
+            escrow_account_info,
+            destination_account_info: seller_nft_token_account_info,
+            rent_destination_info: seller_account_info,
+            token_program_info,
+        },
+        id,
+    )?;
+
+    metadata.clear_listing();
 
-    metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
     Ok(())
 }
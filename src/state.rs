@@ -1,22 +1,143 @@
 use {
-    crate::{error::MetadataError, utils::try_from_slice_checked},
+    crate::{
+        error::MetadataError,
+        utils::{try_from_slice_checked, try_from_slice_uninitialized},
+    },
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
-        pubkey::Pubkey,
+        account_info::AccountInfo, borsh::try_from_slice_unchecked, entrypoint::ProgramResult,
+        program_error::ProgramError, pubkey::Pubkey,
     },
 };
 /// prefix used for PDAs to avoid certain collision attacks (https://en.wikipedia.org/wiki/Collision_attack#Chosen-prefix_collision_attack)
 pub const PREFIX: &str = "metadata";
 
+/// seed of the program-wide config PDA which allocates NFT ids
+pub const CONFIG: &str = "config";
+
+/// seed of the per NFT token account PDA holding listed tokens
+pub const ESCROW: &str = "escrow";
+
+/// seed of the bid PDAs, keyed by NFT id and bidder, holding the offered lamports
+pub const BID: &str = "bid";
+
+/// seed of the per NFT auction PDA, holding the top bid while the auction runs
+pub const AUCTION: &str = "auction";
+
+/// seed of the collection PDAs, followed by the creating authority and the collection name
+pub const COLLECTION: &str = "collection";
+
 
 pub const MAX_NAME_LENGTH: usize = 32;
 
 
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
 pub const MAX_URI_LENGTH: usize = 200;
 
 
+pub const MAX_CREATOR_LIMIT: usize = 5;
+
+
+pub const MAX_CREATOR_LEN: usize = 32 + 1 + 1;
+
+
+/// Layout version written to new NFT accounts, bumped whenever a field is carved out of the padding
+pub const NFT_DATA_VERSION: u8 = 11;
+
+/// Zeroed space at the end of NFT accounts, new fields are appended here so old accounts still decode
+pub const NFT_DATA_PADDING: usize = 150;
+
 pub const MAX_DATA_SIZE: usize =
+    1 // key
+    + 1 // version
+    + 8 // id
+    + 4
+    + MAX_NAME_LENGTH
+    + 4
+    + MAX_URI_LENGTH
+    + 8
+    + 8
+    + 32
+    + 32 // seller
+    + 1 // sale status
+    + 2 // seller fee basis points
+    + 4
+    + MAX_CREATOR_LIMIT * MAX_CREATOR_LEN
+    + 32 // update authority
+    + 1 // is mutable
+    + 1 // allow owner edits
+    + 4
+    + MAX_SYMBOL_LENGTH
+    + 32 // collection
+    + 1 // collection verified
+    + 8 // dutch floor price
+    + 8 // dutch start time
+    + 8 // dutch end time
+    + 8 // listed at
+    + 8 // expires at
+    + 32 // payment mint
+    + NFT_DATA_PADDING;
+
+/// Offset of `collection` in NFT accounts, for memcmp filters on program accounts.
+/// The verified flag is the byte right after, so matching on the collection key
+/// followed by a 1 only returns verified members.
+pub const NFT_COLLECTION_OFFSET: usize =
+    1 // key
+    + 1 // version
+    + 8 // id
+    + 4
+    + MAX_NAME_LENGTH
+    + 4
+    + MAX_URI_LENGTH
+    + 8
+    + 8
+    + 32
+    + 32 // seller
+    + 1 // sale status
+    + 2 // seller fee basis points
+    + 4
+    + MAX_CREATOR_LIMIT * MAX_CREATOR_LEN
+    + 32 // update authority
+    + 1 // is mutable
+    + 1 // allow owner edits
+    + 4
+    + MAX_SYMBOL_LENGTH;
+
+pub const MAX_CONFIG_SIZE: usize = 1 + 8;
+
+/// Highest id of the single byte keyed NFTs, the counter starts above it so MigrateNFTId can re-home them under their own id
+pub const MAX_LEGACY_NFT_ID: u64 = u8::MAX as u64;
+
+/// Zeroed space at the end of collection accounts
+pub const COLLECTION_PADDING: usize = 64;
+
+pub const MAX_BID_SIZE: usize =
+    1 // key
+    + 8 // id
+    + 32 // bidder
+    + 8; // amount
+
+pub const MAX_AUCTION_SIZE: usize =
+    1 // key
+    + 8 // id
+    + 32 // seller
+    + 8 // start time
+    + 8 // end time
+    + 8 // reserve price
+    + 8 // min increment
+    + 8 // top bid
+    + 32; // top bidder
+
+pub const MAX_COLLECTION_SIZE: usize =
+    1 // key
+    + 32 // authority
+    + 4
+    + MAX_NAME_LENGTH
+    + COLLECTION_PADDING;
+
+/// Size of the accounts written before ids were widened to u64
+pub const MAX_LEGACY_DATA_SIZE: usize =
     1
     + 4
     + MAX_NAME_LENGTH
@@ -26,12 +147,44 @@ pub const MAX_DATA_SIZE: usize =
     + 8
     + 32;
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Uninitialized,
+    NFTDataV1,
+    ConfigV1,
+    CollectionV1,
+    BidV1,
+    AuctionV1,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SaleStatus {
+    /// Held by its owner, purchases are rejected whatever the listed price
+    NotForSale,
+    /// Token sits in escrow and can be bought for the listed price
+    Listed,
+    /// Token sits in escrow until the running auction is settled
+    InAuction,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Default)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    // In percentages, NOT basis points ;) Watch out!
+    pub share: u8,
+}
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
 pub struct NFTData {
+    /// Account discriminator
+    pub key: Key,
+    /// Layout version, see `NFT_DATA_VERSION`
+    pub version: u8,
     /// The id for the asset
-    pub id: u8,
+    pub id: u64,
     /// The name of the asset
     pub name: String,
     /// URI pointing to On-chain or CDN token image
@@ -42,12 +195,229 @@ pub struct NFTData {
     pub listed_price: u64,
     /// Address of owner NFT
     pub owner_nft_address: Pubkey,
+    /// Wallet which listed the NFT, its token sits in escrow while listed
+    pub seller: Pubkey,
+    /// Whether the NFT can be purchased
+    pub sale_status: SaleStatus,
+    /// Royalty basis points that goes to creators in secondary sales (0-10000)
+    pub seller_fee_basis_points: u16,
+    /// Array of creators, puffed out to MAX_CREATOR_LIMIT with empty entries
+    pub creators: Vec<Creator>,
+    /// Can edit name, uri and creators, nobody when left as the default key
+    pub update_authority: Pubkey,
+    /// Whether or not the data can be changed by the update authority
+    pub is_mutable: bool,
+    /// Whether the owner may change name and uri, off unless the update authority allows it
+    pub allow_owner_edits: bool,
+    /// Ticker shown by wallets, puffed out to MAX_SYMBOL_LENGTH like the name
+    pub symbol: String,
+    /// Collection account the NFT claims to belong to, none when left as the default key
+    pub collection: Pubkey,
+    /// Set by the collection authority once it confirms the NFT belongs to the collection
+    pub collection_verified: bool,
+    /// Price a declining-price listing bottoms out at, `listed_price` being where it starts
+    pub dutch_floor_price: u64,
+    /// Unix timestamp the price starts declining from `listed_price`
+    pub dutch_start_time: i64,
+    /// Unix timestamp the price reaches the floor, 0 for a fixed-price listing
+    pub dutch_end_time: i64,
+    /// Unix timestamp the listing was opened or UpdateNFTPrice last set its price at, 0 when not listed
+    pub listed_at: i64,
+    /// Unix timestamp from which the listing can no longer be bought and may be cleared, 0 for never
+    pub expires_at: i64,
+    /// SPL mint the listing is priced in, lamports when left as the default key
+    pub payment_mint: Pubkey,
 }
 
 impl NFTData {
+    pub fn is_listed(&self) -> bool {
+        self.sale_status == SaleStatus::Listed
+    }
+
+    pub fn is_in_auction(&self) -> bool {
+        self.sale_status == SaleStatus::InAuction
+    }
+
+    pub fn is_priced_in_token(&self) -> bool {
+        self.payment_mint != Pubkey::default()
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    pub fn is_dutch_listing(&self) -> bool {
+        self.dutch_end_time != 0
+    }
+
+    /// Reset what a listing set once the NFT leaves escrow, `listed_price` stays as the published price
+    pub fn clear_listing(&mut self) {
+        self.seller = Pubkey::default();
+        self.sale_status = SaleStatus::NotForSale;
+        self.dutch_floor_price = 0;
+        self.dutch_start_time = 0;
+        self.dutch_end_time = 0;
+        self.listed_at = 0;
+        self.expires_at = 0;
+        self.payment_mint = Pubkey::default();
+    }
+
+    /// Price a purchase pays at the given unix timestamp.
+    /// A declining-price listing falls linearly from `listed_price` to the floor between its start and end time.
+    pub fn current_price(&self, now: i64) -> Result<u64, ProgramError> {
+        if !self.is_dutch_listing() || now <= self.dutch_start_time {
+            return Ok(self.listed_price);
+        }
+        if now >= self.dutch_end_time {
+            return Ok(self.dutch_floor_price);
+        }
+        let decline = self.listed_price.saturating_sub(self.dutch_floor_price) as u128;
+        let elapsed = now
+            .checked_sub(self.dutch_start_time)
+            .ok_or(MetadataError::NumericalOverflowError)? as u128;
+        let duration = self
+            .dutch_end_time
+            .checked_sub(self.dutch_start_time)
+            .ok_or(MetadataError::NumericalOverflowError)? as u128;
+        Ok(self.listed_price - (decline * elapsed / duration) as u64)
+    }
+
     pub fn from_account_info(a: &AccountInfo) -> Result<NFTData, ProgramError> {
         let md: NFTData =
-            try_from_slice_checked(&a.data.borrow_mut(), MAX_DATA_SIZE)?;
+            try_from_slice_checked(&a.data.borrow_mut(), Key::NFTDataV1, MAX_DATA_SIZE)?;
+
+        if md.version > NFT_DATA_VERSION {
+            return Err(MetadataError::UnsupportedDataVersion.into());
+        }
+
+        Ok(md)
+    }
+
+    /// Decode a freshly allocated NFT account before its key is written
+    pub fn from_uninitialized_account_info(a: &AccountInfo) -> Result<NFTData, ProgramError> {
+        let md: NFTData = try_from_slice_uninitialized(&a.data.borrow_mut(), MAX_DATA_SIZE)?;
+
+        Ok(md)
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Config {
+    /// Account discriminator
+    pub key: Key,
+    /// Number of NFT ids allocated so far, the next NFT gets `nft_count + 1`
+    pub nft_count: u64,
+}
+
+impl Config {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Config, ProgramError> {
+        let config: Config =
+            try_from_slice_checked(&a.data.borrow_mut(), Key::ConfigV1, MAX_CONFIG_SIZE)?;
+
+        Ok(config)
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Collection {
+    /// Account discriminator
+    pub key: Key,
+    /// Only this key can verify NFTs as members of the collection
+    pub authority: Pubkey,
+    /// Name of the collection, puffed out to MAX_NAME_LENGTH
+    pub name: String,
+}
+
+impl Collection {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Collection, ProgramError> {
+        let collection: Collection =
+            try_from_slice_checked(&a.data.borrow_mut(), Key::CollectionV1, MAX_COLLECTION_SIZE)?;
+
+        Ok(collection)
+    }
+}
+
+/// Offer on an NFT, the account holds the offered lamports on top of its rent
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Bid {
+    /// Account discriminator
+    pub key: Key,
+    /// Id of the NFT the bid is for
+    pub id: u64,
+    /// Wallet which placed the bid, gets the NFT when the bid is accepted
+    pub bidder: Pubkey,
+    /// Lamports offered
+    pub amount: u64,
+}
+
+impl Bid {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Bid, ProgramError> {
+        let bid: Bid = try_from_slice_checked(&a.data.borrow_mut(), Key::BidV1, MAX_BID_SIZE)?;
+
+        Ok(bid)
+    }
+}
+
+/// English auction of an NFT, the account holds the top bid on top of its rent
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Auction {
+    /// Account discriminator
+    pub key: Key,
+    /// Id of the NFT being auctioned
+    pub id: u64,
+    /// Wallet which started the auction, gets the proceeds and the NFT back when nobody bid
+    pub seller: Pubkey,
+    /// Unix timestamp from which bids are accepted
+    pub start_time: i64,
+    /// Unix timestamp after which bids are rejected and the auction can be settled
+    pub end_time: i64,
+    /// Lowest first bid in lamports
+    pub reserve_price: u64,
+    /// Lamports every bid must add on top of the current top bid
+    pub min_increment: u64,
+    /// Lamports of the current top bid
+    pub top_bid: u64,
+    /// Wallet of the current top bid, the default key until someone bids
+    pub top_bidder: Pubkey,
+}
+
+impl Auction {
+    pub fn has_bids(&self) -> bool {
+        self.top_bidder != Pubkey::default()
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<Auction, ProgramError> {
+        let auction: Auction =
+            try_from_slice_checked(&a.data.borrow_mut(), Key::AuctionV1, MAX_AUCTION_SIZE)?;
+
+        Ok(auction)
+    }
+}
+
+/// Layout of the NFT accounts keyed by a single byte id, kept around for migration.
+/// These predate the account key, so only their size identifies them.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct LegacyNFTData {
+    pub id: u8,
+    pub name: String,
+    pub uri: String,
+    pub last_price: u64,
+    pub listed_price: u64,
+    pub owner_nft_address: Pubkey,
+}
+
+impl LegacyNFTData {
+    pub fn from_account_info(a: &AccountInfo) -> Result<LegacyNFTData, ProgramError> {
+        let data = a.data.borrow();
+        if data.len() != MAX_LEGACY_DATA_SIZE {
+            return Err(MetadataError::DataTypeMismatch.into());
+        }
+        let md: LegacyNFTData = try_from_slice_unchecked(&data)?;
 
         Ok(md)
     }
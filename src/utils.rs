@@ -3,9 +3,10 @@ use {
         error::MetadataError,
         state::{
             // get_reservation_list, EditionMarker, Key, MasterEditionV1, Metadata, EDITION,
-            // EDITION_MARKER_BIT_SIZE, MAX_CREATOR_LIMIT, MAX_EDITION_LEN, MAX_EDITION_MARKER_SIZE,
-            // MAX_MASTER_EDITION_LEN, MAX_SYMBOL_LENGTH,
-            MAX_URI_LENGTH, MAX_NAME_LENGTH, MAX_DATA_SIZE, PREFIX, NFTData,
+            // EDITION_MARKER_BIT_SIZE, MAX_EDITION_LEN, MAX_EDITION_MARKER_SIZE,
+            // MAX_MASTER_EDITION_LEN,
+            MAX_SYMBOL_LENGTH, MAX_URI_LENGTH, MAX_NAME_LENGTH, MAX_DATA_SIZE, MAX_CONFIG_SIZE, PREFIX, CONFIG,
+            Config, Creator, Key, NFTData, ESCROW, NFT_DATA_VERSION, MAX_CREATOR_LIMIT, MAX_LEGACY_NFT_ID,
         },
     },
     arrayref::{array_ref, array_refs
@@ -23,14 +24,17 @@ use {
         program_pack::{IsInitialized, Pack},
         pubkey::Pubkey,
         system_instruction,
-        sysvar::{rent::Rent, Sysvar},
+        sysvar::{clock::Clock, rent::Rent, Sysvar},
     },
     // metaplex_token_metadata::{
     //     instruction::{create_master_edition, create_metadata_accounts, update_metadata_accounts},
     // },
     spl_token::{
-    //     instruction::{set_authority, AuthorityType},
-        state::{Account},
+        instruction::{initialize_mint, set_authority, AuthorityType},
+        state::{Account, Mint},
+    },
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
     },
     std::convert::TryInto,
 };
@@ -43,10 +47,43 @@ pub fn assert_data_valid(
         return Err(MetadataError::NameTooLong.into());
     }
 
+    if data.symbol.len() > MAX_SYMBOL_LENGTH {
+        return Err(MetadataError::SymbolTooLong.into());
+    }
+
     if data.uri.len() > MAX_URI_LENGTH {
         return Err(MetadataError::UriTooLong.into());
     }
 
+    if data.seller_fee_basis_points > 10000 {
+        return Err(MetadataError::InvalidBasisPoints.into());
+    }
+
+    if data.creators.len() > MAX_CREATOR_LIMIT {
+        return Err(MetadataError::CreatorsTooLong.into());
+    }
+
+    if !data.creators.is_empty() {
+        let mut total: u8 = 0;
+        for i in 0..data.creators.len() {
+            let creator = &data.creators[i];
+            for j in (i + 1)..data.creators.len() {
+                if data.creators[j].address == creator.address {
+                    return Err(MetadataError::DuplicateCreatorAddress.into());
+                }
+            }
+
+            total = total
+                .checked_add(creator.share)
+                .ok_or(MetadataError::NumericalOverflowError)?;
+        }
+
+        if total != 100 {
+            return Err(MetadataError::ShareTotalMustBe100.into());
+        }
+    } else if data.seller_fee_basis_points > 0 {
+        return Err(MetadataError::CreatorsMustBeAtleastOne.into());
+    }
 
     Ok(())
 }
@@ -63,6 +100,104 @@ pub fn assert_initialized<T: Pack + IsInitialized>(
     }
 }
 
+/// assert the token account holds the single token of the NFT mint on behalf of the owner
+pub fn assert_holds_nft(
+    token_account_info: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Account, ProgramError> {
+    assert_owned_by(token_account_info, &spl_token::id())?;
+    let token_account: Account = assert_initialized(token_account_info)?;
+    if token_account.mint != *mint {
+        return Err(MetadataError::OwnerMismatch.into());
+    }
+    if token_account.owner != *owner {
+        return Err(MetadataError::InvalidOwner.into());
+    }
+    if token_account.amount != 1 {
+        return Err(MetadataError::NotEnoughTokens.into());
+    }
+    if token_account.is_frozen() {
+        return Err(MetadataError::TokenAccountFrozen.into());
+    }
+    Ok(token_account)
+}
+
+/// assert the token account can receive the token of the NFT mint on behalf of the owner
+pub fn assert_nft_destination(
+    token_account_info: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Account, ProgramError> {
+    assert_owned_by(token_account_info, &spl_token::id())?;
+    let token_account: Account = assert_initialized(token_account_info)?;
+    if token_account.mint != *mint {
+        return Err(MetadataError::DestinationMintMismatch.into());
+    }
+    if token_account.owner != *owner {
+        return Err(MetadataError::InvalidOwner.into());
+    }
+    Ok(token_account)
+}
+
+/// assert the signer owns the NFT, either by holding its token or by having listed it
+pub fn assert_nft_owner(
+    metadata: &NFTData,
+    owner_account_info: &AccountInfo,
+    owner_nft_token_account_info: &AccountInfo,
+) -> ProgramResult {
+    if metadata.is_listed() {
+        // the token sits in escrow, the wallet which listed it owns the NFT
+        if metadata.seller != *owner_account_info.key {
+            return Err(MetadataError::InvalidOwner.into());
+        }
+    } else {
+        assert_holds_nft(
+            owner_nft_token_account_info,
+            &metadata.owner_nft_address,
+            owner_account_info.key,
+        )?;
+    }
+    Ok(())
+}
+
+/// assert the token account holds tokens of the payment mint on behalf of the owner
+pub fn assert_payment_account(
+    token_account_info: &AccountInfo,
+    payment_mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Account, ProgramError> {
+    assert_owned_by(token_account_info, &spl_token::id())?;
+    let token_account: Account = assert_initialized(token_account_info)?;
+    if token_account.mint != *payment_mint {
+        return Err(MetadataError::PaymentMintMismatch.into());
+    }
+    if token_account.owner != *owner {
+        return Err(MetadataError::InvalidOwner.into());
+    }
+    Ok(token_account)
+}
+
+/// assert the account signed the transaction
+pub fn assert_signer(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.is_signer {
+        Err(MetadataError::AccountIsNotSigner.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// assert the account has not been written by this program yet
+pub fn assert_uninitialized(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.data_is_empty()
+        && account_info.data.borrow()[0] != Key::Uninitialized as u8
+    {
+        Err(MetadataError::AlreadyInitialized.into())
+    } else {
+        Ok(())
+    }
+}
+
 /// Create account almost from scratch, lifted from
 /// https://github.com/solana-labs/solana-program-library/tree/master/associated-token-account/program/src/processor.rs#L51-L98
 #[inline(always)]
@@ -133,6 +268,37 @@ pub fn get_owner_from_token_account(
     Ok(Pubkey::new_from_array(*owner_data))
 }
 
+/// Move lamports out of an account owned by this program
+pub fn transfer_program_lamports(
+    source_info: &AccountInfo,
+    destination_info: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let source_starting_lamports = source_info.lamports();
+    **source_info.lamports.borrow_mut() = source_starting_lamports
+        .checked_sub(amount)
+        .ok_or(MetadataError::NumericalOverflowError)?;
+    let destination_starting_lamports = destination_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_starting_lamports
+        .checked_add(amount)
+        .ok_or(MetadataError::NumericalOverflowError)?;
+    Ok(())
+}
+
+/// Close an account owned by this program, its lamports go to the destination and its data is zeroed
+pub fn close_program_account(
+    account_info: &AccountInfo,
+    destination_info: &AccountInfo,
+) -> ProgramResult {
+    let destination_starting_lamports = destination_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_starting_lamports
+        .checked_add(account_info.lamports())
+        .ok_or(MetadataError::NumericalOverflowError)?;
+    **account_info.lamports.borrow_mut() = 0;
+    account_info.data.borrow_mut().fill(0);
+    Ok(())
+}
+
 pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramResult {
     if !rent.is_exempt(account_info.lamports(), account_info.data_len()) {
         Err(MetadataError::NotRentExempt.into())
@@ -141,7 +307,7 @@ pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramRes
     }
 }
 
-// / TokenMintToParams
+/// TokenMintToParams
 pub struct TokenMintToParams<'a: 'b, 'b> {
     /// mint
     pub mint: AccountInfo<'a>,
@@ -157,6 +323,301 @@ pub struct TokenMintToParams<'a: 'b, 'b> {
     pub token_program: AccountInfo<'a>,
 }
 
+/// Issue a spl_token `MintTo` instruction.
+#[inline(always)]
+pub fn spl_token_mint_to(params: TokenMintToParams<'_, '_>) -> ProgramResult {
+    let TokenMintToParams {
+        mint,
+        destination,
+        authority,
+        token_program,
+        amount,
+        authority_signer_seeds,
+    } = params;
+    let instruction = spl_token::instruction::mint_to(
+        token_program.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+    )?;
+    let account_infos = &[mint, destination, authority, token_program];
+    let result = match authority_signer_seeds {
+        Some(seeds) => invoke_signed(&instruction, account_infos, &[seeds]),
+        None => invoke(&instruction, account_infos),
+    };
+    result.map_err(|_| MetadataError::TokenMintToFailed.into())
+}
+
+/// TokenTransferParams
+pub struct TokenTransferParams<'a: 'b, 'b> {
+    /// source
+    pub source: AccountInfo<'a>,
+    /// destination
+    pub destination: AccountInfo<'a>,
+    /// amount
+    pub amount: u64,
+    /// authority
+    pub authority: AccountInfo<'a>,
+    /// authority_signer_seeds
+    pub authority_signer_seeds: Option<&'b [&'b [u8]]>,
+    /// token_program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Issue a spl_token `Transfer` instruction.
+#[inline(always)]
+pub fn spl_token_transfer(params: TokenTransferParams<'_, '_>) -> ProgramResult {
+    let TokenTransferParams {
+        source,
+        destination,
+        authority,
+        token_program,
+        amount,
+        authority_signer_seeds,
+    } = params;
+    let instruction = spl_token::instruction::transfer(
+        token_program.key,
+        source.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+    )?;
+    let account_infos = &[source, destination, authority, token_program];
+    let result = match authority_signer_seeds {
+        Some(seeds) => invoke_signed(&instruction, account_infos, &[seeds]),
+        None => invoke(&instruction, account_infos),
+    };
+    result.map_err(|_| MetadataError::TokenTransferFailed.into())
+}
+
+/// TokenCloseAccountParams
+pub struct TokenCloseAccountParams<'a: 'b, 'b> {
+    /// account
+    pub account: AccountInfo<'a>,
+    /// destination of the rent lamports
+    pub destination: AccountInfo<'a>,
+    /// authority
+    pub authority: AccountInfo<'a>,
+    /// authority_signer_seeds
+    pub authority_signer_seeds: Option<&'b [&'b [u8]]>,
+    /// token_program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Issue a spl_token `CloseAccount` instruction.
+#[inline(always)]
+pub fn spl_token_close_account(params: TokenCloseAccountParams<'_, '_>) -> ProgramResult {
+    let TokenCloseAccountParams {
+        account,
+        destination,
+        authority,
+        authority_signer_seeds,
+        token_program,
+    } = params;
+    let instruction = spl_token::instruction::close_account(
+        token_program.key,
+        account.key,
+        destination.key,
+        authority.key,
+        &[],
+    )?;
+    let account_infos = &[account, destination, authority, token_program];
+    match authority_signer_seeds {
+        Some(seeds) => invoke_signed(&instruction, account_infos, &[seeds]),
+        None => invoke(&instruction, account_infos),
+    }
+}
+
+/// TokenBurnParams
+pub struct TokenBurnParams<'a: 'b, 'b> {
+    /// mint
+    pub mint: AccountInfo<'a>,
+    /// source
+    pub source: AccountInfo<'a>,
+    /// amount
+    pub amount: u64,
+    /// authority
+    pub authority: AccountInfo<'a>,
+    /// authority_signer_seeds
+    pub authority_signer_seeds: Option<&'b [&'b [u8]]>,
+    /// token_program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Issue a spl_token `Burn` instruction.
+#[inline(always)]
+pub fn spl_token_burn(params: TokenBurnParams<'_, '_>) -> ProgramResult {
+    let TokenBurnParams {
+        mint,
+        source,
+        authority,
+        token_program,
+        amount,
+        authority_signer_seeds,
+    } = params;
+    let instruction = spl_token::instruction::burn(
+        token_program.key,
+        source.key,
+        mint.key,
+        authority.key,
+        &[],
+        amount,
+    )?;
+    let account_infos = &[source, mint, authority, token_program];
+    let result = match authority_signer_seeds {
+        Some(seeds) => invoke_signed(&instruction, account_infos, &[seeds]),
+        None => invoke(&instruction, account_infos),
+    };
+    result.map_err(|_| MetadataError::TokenBurnFailed.into())
+}
+
+pub struct CreateEscrowArgs<'a> {
+    pub metadata_account_info: &'a AccountInfo<'a>,
+    pub escrow_account_info: &'a AccountInfo<'a>,
+    pub mint_info: &'a AccountInfo<'a>,
+    /// Wallet paying the rent of the escrow account
+    pub payer_account_info: &'a AccountInfo<'a>,
+    pub token_program_info: &'a AccountInfo<'a>,
+    pub system_account_info: &'a AccountInfo<'a>,
+    pub rent_info: &'a AccountInfo<'a>,
+}
+
+/// Create the escrow token account holding the NFT token while it is listed or in auction
+pub fn create_escrow(program_id: &Pubkey, accounts: CreateEscrowArgs, id: u64) -> ProgramResult {
+    let CreateEscrowArgs {
+        metadata_account_info,
+        escrow_account_info,
+        mint_info,
+        payer_account_info,
+        token_program_info,
+        system_account_info,
+        rent_info,
+    } = accounts;
+
+    let escrow_bump_seed = assert_derivation(
+        program_id,
+        escrow_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            ESCROW.as_bytes(),
+        ],
+    )?;
+    if !escrow_account_info.data_is_empty() {
+        return Err(MetadataError::AlreadyInitialized.into());
+    }
+    create_or_allocate_account_raw(
+        spl_token::id(),
+        escrow_account_info,
+        rent_info,
+        system_account_info,
+        payer_account_info,
+        Account::LEN,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            ESCROW.as_bytes(),
+            &[escrow_bump_seed],
+        ],
+    )?;
+    // the NFT account is the escrow authority so the program can sign releases
+    invoke(
+        &spl_token::instruction::initialize_account(
+            token_program_info.key,
+            escrow_account_info.key,
+            mint_info.key,
+            metadata_account_info.key,
+        )?,
+        &[
+            escrow_account_info.clone(),
+            mint_info.clone(),
+            metadata_account_info.clone(),
+            rent_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+pub struct ReleaseFromEscrowArgs<'a> {
+    pub metadata_account_info: &'a AccountInfo<'a>,
+    pub escrow_account_info: &'a AccountInfo<'a>,
+    /// Token account receiving the NFT
+    pub destination_account_info: &'a AccountInfo<'a>,
+    /// Wallet receiving the rent of the closed escrow account
+    pub rent_destination_info: &'a AccountInfo<'a>,
+    pub token_program_info: &'a AccountInfo<'a>,
+}
+
+/// Move the NFT token out of its escrow account and close the escrow
+pub fn release_from_escrow(
+    program_id: &Pubkey,
+    accounts: ReleaseFromEscrowArgs,
+    id: u64,
+) -> ProgramResult {
+    let ReleaseFromEscrowArgs {
+        metadata_account_info,
+        escrow_account_info,
+        destination_account_info,
+        rent_destination_info,
+        token_program_info,
+    } = accounts;
+
+    let metadata_bump_seed = assert_derivation(
+        program_id,
+        metadata_account_info,
+        &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()],
+    )?;
+    assert_derivation(
+        program_id,
+        escrow_account_info,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            &id.to_le_bytes(),
+            ESCROW.as_bytes(),
+        ],
+    )?;
+
+    let metadata_authority_signer_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        &[metadata_bump_seed],
+    ];
+    spl_token_transfer(TokenTransferParams {
+        source: escrow_account_info.clone(),
+        destination: destination_account_info.clone(),
+        amount: 1,
+        authority: metadata_account_info.clone(),
+        authority_signer_seeds: Some(metadata_authority_signer_seeds),
+        token_program: token_program_info.clone(),
+    })?;
+    spl_token_close_account(TokenCloseAccountParams {
+        account: escrow_account_info.clone(),
+        destination: rent_destination_info.clone(),
+        authority: metadata_account_info.clone(),
+        authority_signer_seeds: Some(metadata_authority_signer_seeds),
+        token_program: token_program_info.clone(),
+    })
+}
+
+/// assert the account lives at the PDA of the given seeds, returns the bump seed
+pub fn assert_derivation(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    path: &[&[u8]],
+) -> Result<u8, ProgramError> {
+    let (key, bump) = Pubkey::find_program_address(path, program_id);
+    if key != *account.key {
+        return Err(MetadataError::DerivedKeyInvalid.into());
+    }
+    Ok(bump)
+}
 
 pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
     if account.owner != owner {
@@ -169,10 +630,31 @@ pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
 
 pub fn try_from_slice_checked<T: BorshDeserialize>(
     data: &[u8],
+    data_type: Key,
     data_size: usize,
 ) -> Result<T, ProgramError> {
-    if data.len() != data_size
-    {
+    if data.len() != data_size {
+        return Err(MetadataError::DataTypeMismatch.into());
+    }
+    // zeroed accounts would decode as default values, only the exact key is accepted
+    if data[0] != data_type as u8 {
+        if data[0] == Key::Uninitialized as u8 {
+            return Err(MetadataError::Uninitialized.into());
+        }
+        return Err(MetadataError::DataTypeMismatch.into());
+    }
+
+    let result: T = try_from_slice_unchecked(data)?;
+
+    Ok(result)
+}
+
+/// Decode an account this program just allocated, it still holds its zeroed key
+pub fn try_from_slice_uninitialized<T: BorshDeserialize>(
+    data: &[u8],
+    data_size: usize,
+) -> Result<T, ProgramError> {
+    if data.len() != data_size || data[0] != Key::Uninitialized as u8 {
         return Err(MetadataError::DataTypeMismatch.into());
     }
 
@@ -183,38 +665,182 @@ pub fn try_from_slice_checked<T: BorshDeserialize>(
 
 pub struct CreateMetadataAccountsLogicArgs<'a> {
     pub metadata_account_info: &'a AccountInfo<'a>,
-    // pub mint_info: &'a AccountInfo<'a>,
-    // pub mint_authority_info: &'a AccountInfo<'a>,
+    pub config_account_info: &'a AccountInfo<'a>,
+    pub mint_info: &'a AccountInfo<'a>,
+    pub mint_authority_info: &'a AccountInfo<'a>,
+    pub payer_account_info: &'a AccountInfo<'a>,
+    pub update_authority_info: &'a AccountInfo<'a>,
+    pub system_account_info: &'a AccountInfo<'a>,
+    pub rent_info: &'a AccountInfo<'a>,
+}
+
+pub struct CreateNFTMintArgs<'a> {
+    pub mint_info: &'a AccountInfo<'a>,
+    pub mint_authority_info: &'a AccountInfo<'a>,
     pub payer_account_info: &'a AccountInfo<'a>,
-    // pub update_authority_info: &'a AccountInfo<'a>,
+    pub payer_token_account_info: &'a AccountInfo<'a>,
+    pub token_program_info: &'a AccountInfo<'a>,
+    pub associated_token_program_info: &'a AccountInfo<'a>,
     pub system_account_info: &'a AccountInfo<'a>,
     pub rent_info: &'a AccountInfo<'a>,
 }
 
+/// Create a fresh mint, mint its only token to the payer associated token account
+/// and revoke the mint authority so no second token can ever exist
+pub fn create_nft_mint(accounts: CreateNFTMintArgs) -> ProgramResult {
+    let CreateNFTMintArgs {
+        mint_info,
+        mint_authority_info,
+        payer_account_info,
+        payer_token_account_info,
+        token_program_info,
+        associated_token_program_info,
+        system_account_info,
+        rent_info,
+    } = accounts;
+
+    assert_signer(mint_info)?;
+    assert_signer(mint_authority_info)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
+    if *payer_token_account_info.key
+        != get_associated_token_address(payer_account_info.key, mint_info.key)
+    {
+        return Err(MetadataError::DerivedKeyInvalid.into());
+    }
+
+    let rent = &Rent::from_account_info(rent_info)?;
+    invoke(
+        &system_instruction::create_account(
+            payer_account_info.key,
+            mint_info.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        &[
+            payer_account_info.clone(),
+            mint_info.clone(),
+            system_account_info.clone(),
+        ],
+    )?;
+    invoke(
+        &initialize_mint(
+            token_program_info.key,
+            mint_info.key,
+            mint_authority_info.key,
+            None,
+            0,
+        )?,
+        &[
+            mint_info.clone(),
+            rent_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &create_associated_token_account(
+            payer_account_info.key,
+            payer_account_info.key,
+            mint_info.key,
+            token_program_info.key,
+        ),
+        &[
+            payer_account_info.clone(),
+            payer_token_account_info.clone(),
+            payer_account_info.clone(),
+            mint_info.clone(),
+            system_account_info.clone(),
+            token_program_info.clone(),
+            rent_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+
+    spl_token_mint_to(TokenMintToParams {
+        mint: mint_info.clone(),
+        destination: payer_token_account_info.clone(),
+        amount: 1,
+        authority: mint_authority_info.clone(),
+        authority_signer_seeds: None,
+        token_program: token_program_info.clone(),
+    })?;
+    invoke(
+        &set_authority(
+            token_program_info.key,
+            mint_info.key,
+            None,
+            AuthorityType::MintTokens,
+            mint_authority_info.key,
+            &[],
+        )?,
+        &[
+            mint_info.clone(),
+            mint_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
 /// Create a new account instruction
 pub fn process_create_metadata_accounts_logic(
     program_id: &Pubkey,
     accounts: CreateMetadataAccountsLogicArgs,
     data: NFTData,
-    id: u8,
     // allow_direct_creator_writes: bool,
-    // is_mutable: bool,
+    is_mutable: bool,
 ) -> ProgramResult {
     let CreateMetadataAccountsLogicArgs {
         metadata_account_info,
-        // mint_info,
-        // mint_authority_info,
+        config_account_info,
+        mint_info,
+        mint_authority_info,
         payer_account_info,
-        // update_authority_info,
+        update_authority_info,
         system_account_info,
         rent_info,
     } = accounts;
 
+    assert_signer(payer_account_info)?;
+
+    // the NFT has to be a real 1 of 1 token
+    assert_owned_by(mint_info, &spl_token::id())?;
+    let mint: Mint = assert_initialized(mint_info)?;
+    if data.owner_nft_address != *mint_info.key {
+        return Err(MetadataError::MintMismatch.into());
+    }
+    if mint.decimals != 0 {
+        return Err(MetadataError::NFTMintDecimalsShouldBeZero.into());
+    }
+    if mint.supply != 1 {
+        return Err(MetadataError::NFTMintSupplyMustBeOne.into());
+    }
+    // once the authority is revoked nobody can mint more, so there is nobody left to sign
+    if let COption::Some(mint_authority) = mint.mint_authority {
+        if mint_authority != *mint_authority_info.key {
+            return Err(MetadataError::InvalidMintAuthority.into());
+        }
+        if !mint_authority_info.is_signer {
+            return Err(MetadataError::NotMintAuthority.into());
+        }
+    }
+
+    let id = allocate_nft_id(
+        program_id,
+        config_account_info,
+        payer_account_info,
+        system_account_info,
+        rent_info,
+    )?;
+
     let metadata_seeds = &[
         PREFIX.as_bytes(),
         program_id.as_ref(),
         // mint_info.key.as_ref(),
-        &[id],
+        &id.to_le_bytes(),
     ];
     let (metadata_key, metadata_bump_seed) =
         Pubkey::find_program_address(metadata_seeds, program_id);
@@ -222,13 +848,23 @@ pub fn process_create_metadata_accounts_logic(
         PREFIX.as_bytes(),
         program_id.as_ref(),
         // mint_info.key.as_ref(),
-        &[id],
+        &id.to_le_bytes(),
         &[metadata_bump_seed],
     ];
 
     if *metadata_account_info.key != metadata_key {
-        return Err(MetadataError::InvalidMetadataKey.into());
+        // an account already written for this mint means the create is being replayed
+        if metadata_account_info.owner == program_id {
+            if let Ok(existing) = NFTData::from_account_info(metadata_account_info) {
+                if existing.owner_nft_address == *mint_info.key {
+                    return Err(MetadataError::AlreadyInitialized.into());
+                }
+            }
+        }
+        // a concurrent create took the id the caller derived, the caller has to derive the account again
+        return Err(MetadataError::StaleNFTId.into());
     }
+    assert_uninitialized(metadata_account_info)?;
     
     create_or_allocate_account_raw(
         *program_id,
@@ -240,27 +876,119 @@ pub fn process_create_metadata_accounts_logic(
         metadata_authority_signer_seeds,
     )?;
     
-    let mut metadata = NFTData::from_account_info(metadata_account_info)?;
+    let mut metadata = NFTData::from_uninitialized_account_info(metadata_account_info)?;
     
     assert_data_valid(
         &data,
         &metadata,
     )?;
     
+    metadata.key = Key::NFTDataV1;
+    metadata.version = NFT_DATA_VERSION;
+    metadata.id = id;
     metadata.name = data.name;
+    metadata.symbol = data.symbol;
+    // only the collection authority can confirm the membership, see VerifyCollection
+    metadata.collection = data.collection;
+    metadata.collection_verified = false;
     metadata.uri = data.uri;
     metadata.last_price = data.last_price;
     metadata.listed_price = data.listed_price;
     metadata.owner_nft_address = data.owner_nft_address;
-
+    metadata.seller_fee_basis_points = data.seller_fee_basis_points;
+    // Only the payer signed, every other creator has to verify with SignMetadata
+    metadata.creators = data
+        .creators
+        .into_iter()
+        .map(|creator| Creator {
+            verified: creator.verified && creator.address == *payer_account_info.key,
+            ..creator
+        })
+        .collect();
+    metadata.update_authority = *update_authority_info.key;
+    metadata.is_mutable = is_mutable;
+    metadata.allow_owner_edits = data.allow_owner_edits;
 
     puff_out_data_fields(&mut metadata);
 
-    metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+    metadata.serialize(&mut &mut metadata_account_info.data.borrow_mut()[..])?;
     msg!("--> metadata saved");
     Ok(())
 }
 
+/// Load the program-wide config, the account is created on first use
+pub fn load_or_create_config<'a>(
+    program_id: &Pubkey,
+    config_account_info: &AccountInfo<'a>,
+    payer_account_info: &AccountInfo<'a>,
+    system_account_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+) -> Result<Config, ProgramError> {
+    let config_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        CONFIG.as_bytes(),
+    ];
+    let (config_key, config_bump_seed) =
+        Pubkey::find_program_address(config_seeds, program_id);
+    if *config_account_info.key != config_key {
+        return Err(MetadataError::InvalidConfigKey.into());
+    }
+
+    if config_account_info.data_is_empty() {
+        let config_authority_signer_seeds = &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            CONFIG.as_bytes(),
+            &[config_bump_seed],
+        ];
+        create_or_allocate_account_raw(
+            *program_id,
+            config_account_info,
+            rent_info,
+            system_account_info,
+            payer_account_info,
+            MAX_CONFIG_SIZE,
+            config_authority_signer_seeds,
+        )?;
+
+        // the caller writes the bumped counter, nothing to read back yet
+        return Ok(Config {
+            key: Key::ConfigV1,
+            nft_count: MAX_LEGACY_NFT_ID,
+        });
+    }
+    assert_owned_by(config_account_info, program_id)?;
+
+    Config::from_account_info(config_account_info)
+}
+
+/// Bump the program-wide counter and return the id for a new NFT
+pub fn allocate_nft_id<'a>(
+    program_id: &Pubkey,
+    config_account_info: &AccountInfo<'a>,
+    payer_account_info: &AccountInfo<'a>,
+    system_account_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+) -> Result<u64, ProgramError> {
+    let mut config = load_or_create_config(
+        program_id,
+        config_account_info,
+        payer_account_info,
+        system_account_info,
+        rent_info,
+    )?;
+    // counters created before the legacy ids were reserved jump past them too
+    config.nft_count = config
+        .nft_count
+        .max(MAX_LEGACY_NFT_ID)
+        .checked_add(1)
+        .ok_or(MetadataError::NumericalOverflowError)?;
+    config.serialize(&mut &mut config_account_info.data.borrow_mut()[..])?;
+
+    Ok(config.nft_count)
+}
+
 pub fn puff_out_data_fields(metadata: &mut NFTData) {
     let mut array_of_zeroes = vec![];
     while array_of_zeroes.len() < MAX_NAME_LENGTH - metadata.name.len() {
@@ -269,96 +997,244 @@ pub fn puff_out_data_fields(metadata: &mut NFTData) {
     metadata.name =
         metadata.name.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
 
+    let mut array_of_zeroes = vec![];
+    while array_of_zeroes.len() < MAX_SYMBOL_LENGTH - metadata.symbol.len() {
+        array_of_zeroes.push(0u8);
+    }
+    metadata.symbol =
+        metadata.symbol.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+
     let mut array_of_zeroes = vec![];
     while array_of_zeroes.len() < MAX_URI_LENGTH - metadata.uri.len() {
         array_of_zeroes.push(0u8);
     }
     metadata.uri = metadata.uri.clone() + std::str::from_utf8(&array_of_zeroes).unwrap();
+
+    while metadata.creators.len() < MAX_CREATOR_LIMIT {
+        metadata.creators.push(Creator::default());
+    }
+}
+
+/// Creators can only verify or unverify themselves, everyone else keeps the flag they had.
+pub fn assert_creator_verification_unchanged(
+    new_creators: &[Creator],
+    existing_creators: &[Creator],
+    signer: &Pubkey,
+) -> ProgramResult {
+    for creator in new_creators.iter().filter(|c| c.verified && c.address != *signer) {
+        if !existing_creators
+            .iter()
+            .any(|existing| existing.address == creator.address && existing.verified)
+        {
+            return Err(MetadataError::CannotVerifyAnotherCreator.into());
+        }
+    }
+
+    for existing in existing_creators.iter().filter(|c| c.verified && c.address != *signer) {
+        if !new_creators
+            .iter()
+            .any(|creator| creator.address == existing.address && creator.verified)
+        {
+            return Err(MetadataError::CannotUnverifyAnotherCreator.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Split a sale price into the royalty owed to each creator and the rest for the seller.
+/// Empty creator slots get nothing; the seller keeps whatever rounding leaves over.
+pub fn calculate_royalties(
+    nftdata: &NFTData,
+    price: u64,
+) -> Result<(Vec<(Pubkey, u64)>, u64), ProgramError> {
+    let royalty = (price as u128)
+        .checked_mul(nftdata.seller_fee_basis_points as u128)
+        .ok_or(MetadataError::NumericalOverflowError)?
+        / 10000;
+
+    let mut payouts = vec![];
+    let mut paid: u64 = 0;
+    for creator in nftdata.creators.iter().filter(|c| c.share > 0) {
+        let amount = (royalty * creator.share as u128 / 100) as u64;
+        paid = paid
+            .checked_add(amount)
+            .ok_or(MetadataError::NumericalOverflowError)?;
+        payouts.push((creator.address, amount));
+    }
+
+    let remainder = price
+        .checked_sub(paid)
+        .ok_or(MetadataError::NumericalOverflowError)?;
+    Ok((payouts, remainder))
 }
 
 pub struct PurchaseNFTLogicArgs<'a> {
     pub nftdata_account_info: &'a AccountInfo<'a>,
     pub payer_account_info: &'a AccountInfo<'a>,
     pub nft_owner_address_info: &'a AccountInfo<'a>,
-    pub nft_account_info: &'a AccountInfo<'a>,
-    pub new_token_mint_address: &'a AccountInfo<'a>,
+    pub escrow_account_info: &'a AccountInfo<'a>,
+    pub buyer_nft_account_info: &'a AccountInfo<'a>,
+    pub token_program_info: &'a AccountInfo<'a>,
     pub system_account_info: &'a AccountInfo<'a>,
-    pub rent_info: &'a AccountInfo<'a>,
+    pub clock_info: &'a AccountInfo<'a>,
+    /// Wallets of the creators with a share, in the order they are stored on the NFT.
+    /// With a payment mint: buyer and seller payment accounts, then the creator payment accounts.
+    pub remaining_account_infos: &'a [AccountInfo<'a>],
 }
 
 /// Create a new account instruction
 pub fn process_purchase_nft_logic(
     program_id: &Pubkey,
     accounts: PurchaseNFTLogicArgs,
-    id: u8,
-    new_name: Option<String>,
-    new_uri: Option<String>,
+    id: u64,
     price: Option<u64>,
+    max_price: u64,
 ) -> ProgramResult {
     let PurchaseNFTLogicArgs {
         nftdata_account_info,
         payer_account_info,
         nft_owner_address_info,
-        nft_account_info,
-        new_token_mint_address,
+        escrow_account_info,
+        buyer_nft_account_info,
+        token_program_info,
         system_account_info,
-        rent_info,
+        clock_info,
+        remaining_account_infos,
     } = accounts;
 
+    assert_signer(payer_account_info)?;
+
     let nftdata_seeds = &[
         PREFIX.as_bytes(),
         program_id.as_ref(),
-        &[id],
+        &id.to_le_bytes(),
     ];
 
-    let (nftdata_key, nftdata_bump_seed) =
+    let (nftdata_key, _) =
         Pubkey::find_program_address(nftdata_seeds, program_id);
  
     if nftdata_account_info.key != &nftdata_key {
         return Err(MetadataError::InvalidMetadataKey.into());
     }
+    assert_owned_by(nftdata_account_info, program_id)?;
+    if *token_program_info.key != spl_token::id() {
+        return Err(MetadataError::InvalidTokenProgram.into());
+    }
     
     let mut nftdata = NFTData::from_account_info(nftdata_account_info)?;
-    let token_account: Account = assert_initialized(&nft_account_info)?;
-    msg!("--> received: {}, generated: {}", nft_owner_address_info.key, token_account.owner);
-    if nft_owner_address_info.key != &token_account.owner {
+    if !nftdata.is_listed() {
+        return Err(MetadataError::NFTNotListed.into());
+    }
+    msg!("--> received: {}, listed by: {}", nft_owner_address_info.key, nftdata.seller);
+    if *nft_owner_address_info.key != nftdata.seller {
         return Err(MetadataError::OwnerMismatch.into());
     }
-    
-    msg!("---> NFT Owner address: {}, Retrieved: {}", token_account.mint, nftdata.owner_nft_address);
-    if nftdata.owner_nft_address != token_account.mint {
-        return Err(MetadataError::InvalidOwner.into());
+    assert_nft_destination(
+        buyer_nft_account_info,
+        &nftdata.owner_nft_address,
+        payer_account_info.key,
+    )?;
+
+    let clock = Clock::from_account_info(clock_info)?;
+    if nftdata.is_expired(clock.unix_timestamp) {
+        return Err(MetadataError::ListingExpired.into());
+    }
+    let current_price = nftdata.current_price(clock.unix_timestamp)?;
+    if current_price > max_price {
+        msg!("--> Listed price {} is above the maximum {}", current_price, max_price);
+        return Err(MetadataError::PriceAboveMaximum.into());
     }
 
-    msg!("--> Transfer {} lamports to the new account", nftdata.listed_price);
-    invoke(
-        &system_instruction::transfer(&payer_account_info.key, &nft_owner_address_info.key, nftdata.listed_price as u64),
-        &[
-            payer_account_info.clone(),
-            nft_owner_address_info.clone(),
-            system_account_info.clone(),
-        ],
-    )?;
+    let (payouts, seller_amount) = calculate_royalties(&nftdata, current_price)?;
+    if nftdata.is_priced_in_token() {
+        if remaining_account_infos.len() < 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (payment_account_infos, creator_account_infos) = remaining_account_infos.split_at(2);
+        let payer_payment_account_info = &payment_account_infos[0];
+        let seller_payment_account_info = &payment_account_infos[1];
+        assert_payment_account(payer_payment_account_info, &nftdata.payment_mint, payer_account_info.key)?;
+        assert_payment_account(seller_payment_account_info, &nftdata.payment_mint, &nftdata.seller)?;
 
-    
-    // metadata.id = data.id;
-    nftdata.name = match new_name {
-        Some(new_name) => {
-            new_name
+        if creator_account_infos.len() != payouts.len() {
+            return Err(MetadataError::CreatorNotFound.into());
         }
-        None => {
-            nftdata.name
+        for ((address, amount), creator_account_info) in payouts.iter().zip(creator_account_infos) {
+            assert_payment_account(creator_account_info, &nftdata.payment_mint, address)?;
+            if *amount == 0 {
+                continue;
+            }
+            msg!("--> Pay {} tokens of royalties to {}", amount, address);
+            spl_token_transfer(TokenTransferParams {
+                source: payer_payment_account_info.clone(),
+                destination: creator_account_info.clone(),
+                amount: *amount,
+                authority: payer_account_info.clone(),
+                authority_signer_seeds: None,
+                token_program: token_program_info.clone(),
+            })?;
         }
-    };
-    nftdata.uri = match new_uri {
-        Some(new_uri) => {
-            new_uri
+
+        msg!("--> Transfer {} tokens to the seller", seller_amount);
+        spl_token_transfer(TokenTransferParams {
+            source: payer_payment_account_info.clone(),
+            destination: seller_payment_account_info.clone(),
+            amount: seller_amount,
+            authority: payer_account_info.clone(),
+            authority_signer_seeds: None,
+            token_program: token_program_info.clone(),
+        })?;
+    } else {
+        let creator_account_infos = remaining_account_infos;
+        if creator_account_infos.len() != payouts.len() {
+            return Err(MetadataError::CreatorNotFound.into());
         }
-        None => {
-            nftdata.uri
+        for ((address, amount), creator_account_info) in payouts.iter().zip(creator_account_infos) {
+            if creator_account_info.key != address {
+                return Err(MetadataError::CreatorNotFound.into());
+            }
+            if *amount == 0 {
+                continue;
+            }
+            msg!("--> Pay {} lamports of royalties to {}", amount, address);
+            invoke(
+                &system_instruction::transfer(payer_account_info.key, address, *amount),
+                &[
+                    payer_account_info.clone(),
+                    creator_account_info.clone(),
+                    system_account_info.clone(),
+                ],
+            )?;
         }
-    };
-    nftdata.last_price = nftdata.listed_price;
+
+        msg!("--> Transfer {} lamports to the new account", seller_amount);
+        invoke(
+            &system_instruction::transfer(&payer_account_info.key, &nft_owner_address_info.key, seller_amount),
+            &[
+                payer_account_info.clone(),
+                nft_owner_address_info.clone(),
+                system_account_info.clone(),
+            ],
+        )?;
+    }
+
+    msg!("--> Release NFT from escrow to the buyer");
+    release_from_escrow(
+        program_id,
+        ReleaseFromEscrowArgs {
+            metadata_account_info: nftdata_account_info,
+            escrow_account_info,
+            destination_account_info: buyer_nft_account_info,
+            rent_destination_info: nft_owner_address_info,
+            token_program_info,
+        },
+        id,
+    )?;
+    nftdata.clear_listing();
+    
+    // metadata.id = data.id;
+    nftdata.last_price = current_price;
     nftdata.listed_price = match price {
         Some(price) => {
             price
@@ -367,12 +1243,11 @@ pub fn process_purchase_nft_logic(
             nftdata.listed_price
         }
     };
-    nftdata.owner_nft_address = *new_token_mint_address.key;
 
     puff_out_data_fields(&mut nftdata);
 
 
-    nftdata.serialize(&mut *nftdata_account_info.data.borrow_mut())?;
+    nftdata.serialize(&mut &mut nftdata_account_info.data.borrow_mut()[..])?;
     msg!("--> metadata replaced");
     Ok(())
 }
@@ -3,13 +3,36 @@
 use {
     clap::{crate_description, crate_name, crate_version, App, Arg, ArgMatches, SubCommand},
     metaplex_token_metadata::{
+        error::MetadataError,
         instruction::{
             create_metadata_accounts,
+            find_next_metadata_account,
             update_nft_price,
             purchase_nft,
+            list_nft,
+            list_nft_dutch,
+            delist_nft,
+            migrate_nft_id,
+            sign_metadata,
+            remove_creator_verification,
+            update_metadata,
+            owner_update_metadata,
+            create_collection,
+            verify_collection,
+            unverify_collection,
+            close_nft,
+            burn_nft,
+            place_bid,
+            cancel_bid,
+            accept_bid,
+            start_auction,
+            place_auction_bid,
+            settle_auction,
+            clear_expired_listing,
         },
         state::{
-            NFTData, PREFIX,
+            Auction, Bid, Collection, Config, Creator, Key, NFTData, SaleStatus, AUCTION, BID, COLLECTION, CONFIG, MAX_DATA_SIZE,
+            NFT_COLLECTION_OFFSET, PREFIX,
             // MAX_SYMBOL_LENGTH, MAX_NAME_LENGTH, MAX_URI_LENGTH,
         },
     },
@@ -38,7 +61,13 @@ use {
         instruction::{initialize_account, initialize_mint, mint_to},
         state::{Account as TokenAccount, Mint},
     },
-    std::str::FromStr,
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
+    },
+    std::{
+        str::FromStr,
+        time::{SystemTime, UNIX_EPOCH},
+    },
 };
 use solana_account_decoder::{
     parse_account_data::{parse_account_data, AccountAdditionalData, ParsedAccount},
@@ -57,50 +86,101 @@ fn create_metadata_account_call(
     let program_key = metaplex_token_metadata::id();
     println!("--->Program_id: {}\n", program_key);
 
-    let accounts = client.get_program_accounts(&program_key).unwrap();
-    println!("--> Saved nft accounts: {}", accounts.len());
-    let id = accounts.len() as u8 + 1;
-    // let id = app_matches.value_of("id").unwrap().parse::<u8>().unwrap();
     let last_price = 0 as u64;
     let listed_price = (app_matches.value_of("listed_price").unwrap().parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
     let name = app_matches.value_of("name").unwrap().to_owned();
-    // let symbol = app_matches.value_of("symbol").unwrap().to_owned();
+    let symbol = app_matches.value_of("symbol").unwrap_or("").to_owned();
     let uri = app_matches.value_of("uri").unwrap().to_owned();
 
-    let owner_key = pubkey_of(app_matches, "owner").unwrap();
-    println!("--->\n Id: {},\n Name: {},\n Uri: {},\n Last_price: {},\n Listed_price: {},\n Owner: {}\n",
-        id, name, uri, last_price, listed_price, owner_key
-    );
-
-    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
-    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
-    println!("---> Generated nft Id: {}", metadata_key);
-
-    let new_metadata_instruction = create_metadata_accounts(
-        program_key,
-        metadata_key,
-        payer.pubkey(),
-        id,
-        name,
-        uri,
-        last_price,
-        listed_price,
-        owner_key,
+    // a fresh mint is created by the program when minting the token, its keypair signs the creation
+    let mint_token = app_matches.is_present("mint_token");
+    let new_mint = Keypair::new();
+    let owner_key = if mint_token {
+        new_mint.pubkey()
+    } else {
+        pubkey_of(app_matches, "owner").unwrap()
+    };
+    let seller_fee_basis_points = app_matches
+        .value_of("seller_fee_basis_points")
+        .unwrap_or("0")
+        .parse::<u16>()
+        .unwrap();
+    // each creator is given as ADDRESS:SHARE, shares must add up to 100
+    let creators: Vec<Creator> = match app_matches.values_of("creator") {
+        Some(values) => values
+            .map(|val| {
+                let mut parts = val.split(':');
+                let address = Pubkey::from_str(parts.next().unwrap()).unwrap();
+                let share = parts.next().unwrap().parse::<u8>().unwrap();
+                Creator { address, verified: false, share }
+            })
+            .collect(),
+        None => vec![],
+    };
+    println!("--->\n Name: {},\n Symbol: {},\n Uri: {},\n Last_price: {},\n Listed_price: {},\n Owner: {},\n Royalties: {} bps to {} creators\n",
+        name, symbol, uri, last_price, listed_price, owner_key, seller_fee_basis_points, creators.len()
     );
 
-    let mut instructions = vec![];
+    // the update authority does not sign the creation, only its key is recorded
+    let update_authority_key = match app_matches.value_of("update_authority") {
+        Some(path) => read_keypair_file(path).unwrap().pubkey(),
+        None => payer.pubkey(),
+    };
+    let is_mutable = !app_matches.is_present("immutable");
+    let allow_owner_edits = app_matches.is_present("allow_owner_edits");
+    let collection = pubkey_of(app_matches, "collection");
 
-    // if create_new_mint {
-    //     instructions.append(&mut new_mint_instructions)
-    // }
+    // the program assigns ids from the config counter, the next one is only needed to derive the account
+    let config_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), CONFIG.as_bytes()];
+    let (config_key, _) = Pubkey::find_program_address(config_seeds, &program_key);
+    let stale_id_error = format!("custom program error: {:#x}", MetadataError::StaleNFTId as u32);
+    let mut attempts = 0;
+    let metadata_key = loop {
+        let config = client
+            .get_account(&config_key)
+            .ok()
+            .map(|account| try_from_slice_unchecked::<Config>(&account.data).unwrap());
+        let (metadata_key, id) = find_next_metadata_account(&program_key, config.as_ref());
+        println!("---> Generated nft Id: {} - {}", id, metadata_key);
 
-    instructions.push(new_metadata_instruction);
+        // the keypair signs as the mint authority, unless the mint authority was already revoked
+        let instructions = vec![create_metadata_accounts(
+            program_key,
+            metadata_key,
+            owner_key,
+            payer.pubkey(),
+            payer.pubkey(),
+            update_authority_key,
+            name.clone(),
+            symbol.clone(),
+            uri.clone(),
+            last_price,
+            listed_price,
+            seller_fee_basis_points,
+            creators.clone(),
+            is_mutable,
+            allow_owner_edits,
+            collection,
+            mint_token,
+        )];
 
-    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
-    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
-    let signers = vec![&payer];
-    transaction.sign(&signers, recent_blockhash);
-    client.send_and_confirm_transaction(&transaction).unwrap();
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+        let mut signers = vec![&payer];
+        if mint_token {
+            signers.push(&new_mint);
+        }
+        transaction.sign(&signers, recent_blockhash);
+        match client.send_and_confirm_transaction(&transaction) {
+            Ok(_) => break metadata_key,
+            // someone else created an NFT with this id first, take the next one
+            Err(err) if attempts < 3 && err.to_string().contains(&stale_id_error) => {
+                attempts += 1;
+                println!("---> Id {} was taken meanwhile, retrying", id);
+            }
+            Err(err) => panic!("{}", err),
+        }
+    };
     let account = client.get_account(&metadata_key).unwrap();
     let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
     println!("---> Retrived NFT Data: {}", metadata.name);
@@ -115,22 +195,125 @@ fn update_metadata_account_call(
     let program_key = metaplex_token_metadata::id();
     println!("--->Program_id: {}\n", program_key);
 
-    let id = app_matches.value_of("id").unwrap().parse::<u8>().unwrap();
-    let listed_price = (app_matches.value_of("listed_price").unwrap().parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let expires_at = app_matches
+        .value_of("expires_in")
+        .map(|expires_in| unix_now() + expires_in.parse::<i64>().unwrap());
 
-    println!("--->\n Id: {},\n Price: {}",id, listed_price);
+    println!("--->\n Id: {},", id);
     
-    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
     let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
     println!("---> Get nft account from id: {}", metadata_key);
     
     let account = client.get_account(&metadata_key).unwrap();
     let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
-    println!("---> Retrived NFT Data: name-{}, price-{}", metadata.name, metadata.listed_price);
+    println!("---> Retrived NFT Data: name-{}, price-{}, status-{:?}", metadata.name, metadata.listed_price, metadata.sale_status);
+    // the new price is in the payment mint of the listing, SOL without one
+    let listed_price = price_in_base_units(&client, &metadata.payment_mint, app_matches.value_of("listed_price").unwrap());
+    println!("   Price: {}", listed_price);
+
+    let (owner, nft_owner_account) = find_nft_holder(&client, &metadata.owner_nft_address);
+    println!("--> holder {} - {}", owner, nft_owner_account);
+
+    let mut instructions = vec![];
+
+    let new_metadata_instruction = update_nft_price(
+        program_key,
+        metadata_key,
+        id,
+        listed_price,
+        expires_at,
+        payer.pubkey(),
+        nft_owner_account,
+    );
+
+    instructions.push(new_metadata_instruction);
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Updated NFT Data: name-{} new_price-{}", metadata.name, metadata.listed_price);
+    (metadata, metadata_key)
+}
+
+/// Turn a price typed in SOL, or in whole tokens of the payment mint, into base units
+fn price_in_base_units(client: &RpcClient, payment_mint: &Pubkey, price: &str) -> u64 {
+    let price = price.parse::<f64>().unwrap();
+    if *payment_mint == Pubkey::default() {
+        return (price * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+    }
+    let account = client.get_account(payment_mint).unwrap();
+    let mint = Mint::unpack(&account.data).unwrap();
+    (price * 10u64.pow(mint.decimals as u32) as f64).round() as u64
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+}
+
+fn get_all_nfts(
+    client: &RpcClient,
+    collection: Option<Pubkey>,
+) {
+    let program_key = metaplex_token_metadata::id();
+    let accounts = match collection {
+        Some(collection) => {
+            // let the RPC node pick the members out by the collection key at its fixed offset
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(MAX_DATA_SIZE as u64),
+                    RpcFilterType::Memcmp(Memcmp {
+                        offset: NFT_COLLECTION_OFFSET,
+                        bytes: MemcmpEncodedBytes::Binary(collection.to_string()),
+                        encoding: None,
+                    }),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: None,
+                    commitment: None,
+                },
+                with_context: None,
+            };
+            client.get_program_accounts_with_config(&program_key, config).unwrap()
+        }
+        None => client.get_program_accounts(&program_key).unwrap(),
+    };
+    println!("--> Saved program accounts: {}", accounts.len());
+
+    for (pubkey, account) in accounts {
+        // skip the config counter, collections and accounts still waiting for migration
+        if account.data.len() != MAX_DATA_SIZE || account.data[0] != Key::NFTDataV1 as u8 {
+            continue;
+        }
+        let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+        // anyone can point an NFT at a collection, only verified members count
+        if collection.is_some() && !metadata.collection_verified {
+            continue;
+        }
+        println!("nft_account: {:?}", pubkey);
+        println!("data: {:?}", metadata);
+        if metadata.sale_status == SaleStatus::Listed {
+            if metadata.is_priced_in_token() {
+                println!("current price: {} of mint {}", metadata.current_price(unix_now()).unwrap(), metadata.payment_mint);
+            } else {
+                println!("current price: {} lamports", metadata.current_price(unix_now()).unwrap());
+            }
+        }
+    }
+}
 
+/// Find the wallet and token account currently holding the NFT of the given mint
+fn find_nft_holder(client: &RpcClient, mint: &Pubkey) -> (Pubkey, Pubkey) {
     let filter1 = RpcFilterType::Memcmp(Memcmp {
         offset: 0,
-        bytes: MemcmpEncodedBytes::Binary(metadata.owner_nft_address.to_string()),
+        bytes: MemcmpEncodedBytes::Binary(mint.to_string()),
         encoding: None,
     });
     let filter2 = RpcFilterType::DataSize(165);
@@ -154,7 +337,7 @@ fn update_metadata_account_call(
     println!("---> Captured holder count: {}", holders.len());
     for (holder_address, holder_account) in holders {
         let data = parse_account_data(
-            &metadata.owner_nft_address,
+            mint,
             &spl_token::id(),
             &holder_account.data,
             Some(AccountAdditionalData {
@@ -170,45 +353,7 @@ fn update_metadata_account_call(
         }
     }
     let owner = Pubkey::from_str(&*nft_owner_key).unwrap();
-    println!("--> holder {} - {}", owner, nft_owner_account);
-
-    let mut instructions = vec![];
-
-    let new_metadata_instruction = update_nft_price(
-        program_key,
-        metadata_key,
-        id,
-        listed_price,
-        payer.pubkey(),
-        nft_owner_account,
-    );
-
-    instructions.push(new_metadata_instruction);
-
-    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
-    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
-    let signers = vec![&payer];
-    transaction.sign(&signers, recent_blockhash);
-    client.send_and_confirm_transaction(&transaction).unwrap();
-
-    let account = client.get_account(&metadata_key).unwrap();
-    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
-    println!("---> Updated NFT Data: name-{} new_price-{}", metadata.name, metadata.listed_price);
-    (metadata, metadata_key)
-}
-
-fn get_all_nfts(
-    client: &RpcClient,
-) {
-    let program_key = metaplex_token_metadata::id();
-    let accounts = client.get_program_accounts(&program_key).unwrap();
-    println!("--> Saved program accounts: {}", accounts.len());
-
-    for (pubkey, account) in accounts {
-        println!("nft_account: {:?}", pubkey);
-        let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
-        println!("data: {:?}", metadata);
-    }
+    (owner, nft_owner_account)
 }
 
 fn parse_token_amount(data: &ParsedAccount) -> Option<u64> {
@@ -247,101 +392,172 @@ fn purchase_nft_call(
     let program_key = metaplex_token_metadata::id();
     println!("--->Program_id: {}\n", program_key);
 
-    let id = app_matches.value_of("id").unwrap().parse::<u8>().unwrap();
-    let listed_price = match app_matches.value_of("listed_price") {
-        Some(_val) => Some((app_matches.value_of("listed_price").unwrap().parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64),
-        None => None,
-    };
-    let uri = match app_matches.value_of("uri") {
-        Some(val) => Some(val.to_owned()),
-        None => None,
-    };
-
-    let name = match app_matches.value_of("name") {
-        Some(val) => Some(val.to_owned()),
-        None => None,
-    };
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
     
     println!("--->\n Id: {},", id);
-    if listed_price != None {
-        println!("   Price: {}", listed_price.unwrap());
-    };
-    // if name != None {
-    //     println!("   Name: {}", name);
-    // }
-    // if uri != None {
-    //     println!("   Uri: {}", uri);
-    // }
     
-    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(),&[id]];
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
     let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
     println!("---> Get nft account from id: {}", metadata_key);
     
     let account = client.get_account(&metadata_key).unwrap();
     let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
     println!("---> Retrived NFT Data: name-{}, price-{}, owner_nft_account-{}", metadata.name, metadata.listed_price, metadata.owner_nft_address);
-    
-    let filter1 = RpcFilterType::Memcmp(Memcmp {
-        offset: 0,
-        bytes: MemcmpEncodedBytes::Binary(metadata.owner_nft_address.to_string()),
-        encoding: None,
-    });
-    let filter2 = RpcFilterType::DataSize(165);
-    let account_config = RpcAccountInfoConfig {
-        encoding: Some(UiAccountEncoding::Base64),
-        data_slice: None,
-        commitment: Some(CommitmentConfig {
-            commitment: CommitmentLevel::Confirmed,
-        }),
+    // prices are in the payment mint of the listing, SOL without one
+    let listed_price = app_matches
+        .value_of("listed_price")
+        .map(|val| price_in_base_units(&client, &metadata.payment_mint, val));
+    if listed_price != None {
+        println!("   Price: {}", listed_price.unwrap());
     };
+    if metadata.sale_status != SaleStatus::Listed {
+        println!("---> NFT {} is not for sale", id);
+        return (metadata, metadata_key);
+    }
+    if metadata.is_expired(unix_now()) {
+        println!("---> The listing of NFT {} has expired", id);
+        return (metadata, metadata_key);
+    }
+    println!("--> seller {}", metadata.seller);
 
-    let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter1, filter2]),
-        account_config,
-        with_context: None,
+    // never pay more than the price shown here, even if the seller changes it meanwhile
+    let max_price = match app_matches.value_of("max_price") {
+        Some(val) => price_in_base_units(&client, &metadata.payment_mint, val),
+        // a declining price only gets lower until the purchase lands
+        None => metadata.current_price(unix_now()).unwrap(),
     };
 
-    let mut nft_owner_key: String = String::new();
-    let mut nft_owner_account: Pubkey = Pubkey::new_unique();
-    let holders = client.get_program_accounts_with_config(&spl_token::id(), config).unwrap();
-    for (holder_address, holder_account) in holders {
-        let data = parse_account_data(
-            &metadata.owner_nft_address,
+    let mut instructions = vec![];
+
+    // fresh token account for the buyer to receive the NFT
+    let buyer_token_account = Keypair::new();
+    instructions.push(create_account(
+        &payer.pubkey(),
+        &buyer_token_account.pubkey(),
+        client
+            .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)
+            .unwrap(),
+        TokenAccount::LEN as u64,
+        &spl_token::id(),
+    ));
+    instructions.push(
+        initialize_account(
             &spl_token::id(),
-            &holder_account.data,
-            Some(AccountAdditionalData {
-                spl_token_decimals: Some(0),
-            }),
-        ).unwrap();
-        let amount = parse_token_amount(&data).unwrap();
+            &buyer_token_account.pubkey(),
+            &metadata.owner_nft_address,
+            &payer.pubkey(),
+        )
+        .unwrap(),
+    );
 
-        if amount == 1 {
-            let owner_wallet = parse_owner(&data).unwrap();
-            nft_owner_key = owner_wallet;
-            nft_owner_account = holder_address;
+    let creators: Vec<Pubkey> = metadata
+        .creators
+        .iter()
+        .filter(|creator| creator.share > 0)
+        .map(|creator| creator.address)
+        .collect();
+    let (payment_token_accounts, creators) = if metadata.is_priced_in_token() {
+        // the buyer pays out of its own payment account and funds the seller and creator ones when missing
+        let payer_payment_account = get_associated_token_address(&payer.pubkey(), &metadata.payment_mint);
+        let mut receivers = vec![metadata.seller];
+        receivers.extend(creators.iter().cloned());
+        let mut receiver_accounts = vec![];
+        for receiver in receivers {
+            let receiver_account = get_associated_token_address(&receiver, &metadata.payment_mint);
+            if client.get_account(&receiver_account).is_err() {
+                instructions.push(create_associated_token_account(
+                    &payer.pubkey(),
+                    &receiver,
+                    &metadata.payment_mint,
+                    &spl_token::id(),
+                ));
+            }
+            receiver_accounts.push(receiver_account);
         }
-    }
-    let owner = Pubkey::from_str(&*nft_owner_key).unwrap();
-    println!("--> holder {} - {}", owner, nft_owner_account);
-
-    let mut instructions = vec![];
+        let seller_payment_account = receiver_accounts.remove(0);
+        (Some((payer_payment_account, seller_payment_account)), receiver_accounts)
+    } else {
+        (None, creators)
+    };
 
     let new_metadata_instruction = purchase_nft(
         program_key,
         metadata_key,
         id,
-        name,
-        uri,
         listed_price,
+        max_price,
         payer.pubkey(),
-        owner,
-        nft_owner_account,
-        // should be new mint key
-        metadata.owner_nft_address,
+        metadata.seller,
+        buyer_token_account.pubkey(),
+        payment_token_accounts,
+        creators,
     );
 
     instructions.push(new_metadata_instruction);
 
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer, &buyer_token_account];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Updated NFT Data: name-{} nft_token_account-{}", metadata.name, buyer_token_account.pubkey());
+    (metadata, metadata_key)
+}
+
+fn list_nft_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let payment_mint = pubkey_of(app_matches, "payment_mint");
+    let listed_price = price_in_base_units(&client, &payment_mint.unwrap_or_default(), app_matches.value_of("listed_price").unwrap());
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+
+    let (owner, nft_owner_account) = find_nft_holder(&client, &metadata.owner_nft_address);
+    println!("--> holder {} - {}", owner, nft_owner_account);
+
+    let instructions = match app_matches.value_of("floor_price") {
+        Some(floor_price) => {
+            let floor_price = price_in_base_units(&client, &payment_mint.unwrap_or_default(), floor_price);
+            let duration = app_matches.value_of("duration").unwrap().parse::<i64>().unwrap();
+            let start_time = unix_now();
+            vec![list_nft_dutch(
+                program_key,
+                metadata_key,
+                id,
+                listed_price,
+                floor_price,
+                start_time,
+                start_time + duration,
+                payment_mint,
+                payer.pubkey(),
+                nft_owner_account,
+                metadata.owner_nft_address,
+            )]
+        }
+        None => vec![list_nft(
+            program_key,
+            metadata_key,
+            id,
+            listed_price,
+            payment_mint,
+            payer.pubkey(),
+            nft_owner_account,
+            metadata.owner_nft_address,
+        )],
+    };
+
     let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
     let recent_blockhash = client.get_recent_blockhash().unwrap().0;
     let signers = vec![&payer];
@@ -350,125 +566,1298 @@ fn purchase_nft_call(
 
     let account = client.get_account(&metadata_key).unwrap();
     let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
-    println!("---> Updated NFT Data: name-{} new_owner-{}", metadata.name, metadata.owner_nft_address);
+    println!("---> Listed NFT Data: name-{} price-{}", metadata.name, metadata.listed_price);
     (metadata, metadata_key)
 }
 
-fn main() {
-    let app_matches = App::new(crate_name!())
-        .about(crate_description!())
-        .version(crate_version!())
-        .arg(
-            Arg::with_name("keypair")
-                .long("keypair")
-                .value_name("KEYPAIR")
-                .validator(is_valid_signer)
-                .takes_value(true)
-                .global(true)
-                .help("Filepath or URL to a keypair"),
-        )
-        .arg(
-            Arg::with_name("json_rpc_url")
-                .long("url")
-                .value_name("URL")
-                .takes_value(true)
-                .global(true)
-                .validator(is_url)
-                .help("JSON RPC URL for the cluster [default: devnet]"),
+fn delist_nft_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+
+    let mut instructions = vec![];
+
+    // fresh token account for the seller to take the NFT back
+    let seller_token_account = Keypair::new();
+    instructions.push(create_account(
+        &payer.pubkey(),
+        &seller_token_account.pubkey(),
+        client
+            .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)
+            .unwrap(),
+        TokenAccount::LEN as u64,
+        &spl_token::id(),
+    ));
+    instructions.push(
+        initialize_account(
+            &spl_token::id(),
+            &seller_token_account.pubkey(),
+            &metadata.owner_nft_address,
+            &payer.pubkey(),
         )
-        .arg(
-            Arg::with_name("update_authority")
-                .long("update_authority")
-                .value_name("UPDATE_AUTHORITY")
-                .takes_value(true)
-                .global(true)
-                .help("Update authority filepath or url to keypair besides yourself, defaults to normal keypair"),
-        ).subcommand(
-            SubCommand::with_name("create_metadata_accounts")
-                .about("Create Metadata Accounts")
-                .arg(
-                    Arg::with_name("name")
-                        .long("name")
-                        .required(true)
-                        .value_name("NAME")
-                        .takes_value(true)
-                        .help("Name for the NFT"),
+        .unwrap(),
+    );
+    instructions.push(delist_nft(
+        program_key,
+        metadata_key,
+        id,
+        payer.pubkey(),
+        seller_token_account.pubkey(),
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer, &seller_token_account];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Delisted NFT Data: name-{} nft_token_account-{}", metadata.name, seller_token_account.pubkey());
+    (metadata, metadata_key)
+}
+
+fn migrate_nft_id_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let legacy_id = app_matches.value_of("id").unwrap().parse::<u8>().unwrap();
+    let id = legacy_id as u64;
+
+    let legacy_metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &[legacy_id]];
+    let (legacy_metadata_key, _) = Pubkey::find_program_address(legacy_metadata_seeds, &program_key);
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    println!("---> Migrate nft account {} to {}", legacy_metadata_key, metadata_key);
+
+    let instructions = vec![migrate_nft_id(
+        program_key,
+        legacy_metadata_key,
+        metadata_key,
+        payer.pubkey(),
+        legacy_id,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    (metadata, metadata_key)
+}
+
+fn sign_metadata_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+
+    // the keypair signs as the creator
+    let instruction = if app_matches.is_present("remove") {
+        remove_creator_verification(program_key, metadata_key, id, payer.pubkey())
+    } else {
+        sign_metadata(program_key, metadata_key, id, payer.pubkey())
+    };
+    let instructions = vec![instruction];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    for creator in metadata.creators.iter().filter(|creator| creator.share > 0) {
+        println!("---> Creator {} share {} verified {}", creator.address, creator.share, creator.verified);
+    }
+    (metadata, metadata_key)
+}
+
+fn update_metadata_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let update_authority = match app_matches.value_of("update_authority") {
+        Some(path) => read_keypair_file(path).unwrap(),
+        None => Keypair::from_bytes(&payer.to_bytes()).unwrap(),
+    };
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let name = app_matches.value_of("name").map(|val| val.to_owned());
+    let uri = app_matches.value_of("uri").map(|val| val.to_owned());
+    let creators: Option<Vec<Creator>> = app_matches.values_of("creator").map(|values| {
+        values
+            .map(|val| {
+                let mut parts = val.split(':');
+                let address = Pubkey::from_str(parts.next().unwrap()).unwrap();
+                let share = parts.next().unwrap().parse::<u8>().unwrap();
+                Creator { address, verified: false, share }
+            })
+            .collect()
+    });
+    let new_update_authority = pubkey_of(app_matches, "new_update_authority");
+    let allow_owner_edits = app_matches
+        .value_of("allow_owner_edits")
+        .map(|val| val.parse::<bool>().unwrap());
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+
+    // keep the verified flags creators already gave, only the authority itself may change its own
+    let creators = creators.map(|mut creators| {
+        let account = client.get_account(&metadata_key).unwrap();
+        let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+        for creator in creators.iter_mut() {
+            creator.verified = metadata
+                .creators
+                .iter()
+                .any(|existing| existing.address == creator.address && existing.verified);
+        }
+        creators
+    });
+
+    let instructions = vec![update_metadata(
+        program_key,
+        metadata_key,
+        id,
+        update_authority.pubkey(),
+        name,
+        uri,
+        creators,
+        new_update_authority,
+        allow_owner_edits,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer, &update_authority];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Update authority: {}, mutable: {}", metadata.update_authority, metadata.is_mutable);
+    (metadata, metadata_key)
+}
+
+fn edit_nft_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let name = app_matches.value_of("name").map(|val| val.to_owned());
+    let uri = app_matches.value_of("uri").map(|val| val.to_owned());
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    if !metadata.allow_owner_edits {
+        println!("---> NFT {} does not allow the owner to change name or uri", id);
+        return (metadata, metadata_key);
+    }
+
+    // the token account is only checked while the NFT is not listed
+    let owner_token_account = if metadata.sale_status == SaleStatus::Listed {
+        metadata.seller
+    } else {
+        find_nft_holder(&client, &metadata.owner_nft_address).1
+    };
+
+    let instructions = vec![owner_update_metadata(
+        program_key,
+        metadata_key,
+        id,
+        payer.pubkey(),
+        owner_token_account,
+        name,
+        uri,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    (metadata, metadata_key)
+}
+
+fn create_collection_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (Collection, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let name = app_matches.value_of("name").unwrap().to_owned();
+    let collection_seeds = &[
+        PREFIX.as_bytes(),
+        &program_key.as_ref(),
+        COLLECTION.as_bytes(),
+        &payer.pubkey().to_bytes(),
+        name.as_bytes(),
+    ];
+    let (collection_key, _) = Pubkey::find_program_address(collection_seeds, &program_key);
+
+    let instructions = vec![create_collection(program_key, payer.pubkey(), name)];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&collection_key).unwrap();
+    let collection: Collection = try_from_slice_unchecked(&account.data).unwrap();
+    (collection, collection_key)
+}
+
+fn verify_collection_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    if metadata.collection == Pubkey::default() {
+        println!("---> NFT {} does not point at a collection", id);
+        return (metadata, metadata_key);
+    }
+
+    // the keypair signs as the collection authority
+    let instruction = if app_matches.is_present("unverify") {
+        unverify_collection(program_key, metadata_key, id, payer.pubkey(), metadata.collection)
+    } else {
+        verify_collection(program_key, metadata_key, id, payer.pubkey(), metadata.collection)
+    };
+    let instructions = vec![instruction];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Collection {} verified {}", metadata.collection, metadata.collection_verified);
+    (metadata, metadata_key)
+}
+
+fn close_nft_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let recipient = pubkey_of(app_matches, "recipient").unwrap_or_else(|| payer.pubkey());
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    if metadata.sale_status == SaleStatus::Listed {
+        println!("---> NFT {} is listed, delist it first", id);
+        return (metadata, metadata_key);
+    }
+
+    // the token account is only checked when the keypair is not the update authority
+    let owner_token_account = if metadata.update_authority == payer.pubkey() {
+        payer.pubkey()
+    } else {
+        find_nft_holder(&client, &metadata.owner_nft_address).1
+    };
+
+    let instructions = vec![close_nft(
+        program_key,
+        metadata_key,
+        id,
+        payer.pubkey(),
+        owner_token_account,
+        recipient,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    println!("---> Closed NFT {}, rent sent to {}", id, recipient);
+    (metadata, metadata_key)
+}
+
+fn burn_nft_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    if metadata.sale_status == SaleStatus::Listed {
+        println!("---> NFT {} is listed, delist it first", id);
+        return (metadata, metadata_key);
+    }
+
+    let (owner, owner_token_account) = find_nft_holder(&client, &metadata.owner_nft_address);
+    if owner != payer.pubkey() {
+        println!("---> NFT {} is held by {}", id, owner);
+        return (metadata, metadata_key);
+    }
+
+    let instructions = vec![burn_nft(
+        program_key,
+        metadata_key,
+        id,
+        payer.pubkey(),
+        owner_token_account,
+        metadata.owner_nft_address,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    println!("---> Burned NFT {}", id);
+    (metadata, metadata_key)
+}
+
+fn place_bid_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (Bid, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let amount = (app_matches.value_of("amount").unwrap().parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        &program_key.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        &payer.pubkey().to_bytes(),
+    ];
+    let (bid_key, _) = Pubkey::find_program_address(bid_seeds, &program_key);
+
+    let instructions = vec![place_bid(program_key, metadata_key, id, amount, payer.pubkey())];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&bid_key).unwrap();
+    let bid: Bid = try_from_slice_unchecked(&account.data).unwrap();
+    (bid, bid_key)
+}
+
+fn cancel_bid_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let instructions = vec![cancel_bid(program_key, id, payer.pubkey())];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+    println!("---> Cancelled bid on NFT {}", id);
+}
+
+fn accept_bid_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let bidder = pubkey_of(app_matches, "bidder").unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        &program_key.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        &bidder.to_bytes(),
+    ];
+    let (bid_key, _) = Pubkey::find_program_address(bid_seeds, &program_key);
+    let account = client.get_account(&bid_key).unwrap();
+    let bid: Bid = try_from_slice_unchecked(&account.data).unwrap();
+    println!("---> Accepting {} lamports from {}", bid.amount, bidder);
+
+    // the token account is only checked while the NFT is not listed
+    let owner_token_account = if metadata.sale_status == SaleStatus::Listed {
+        metadata.seller
+    } else {
+        find_nft_holder(&client, &metadata.owner_nft_address).1
+    };
+
+    let mut instructions = vec![];
+
+    // the owner pays for the bidder token account when the bidder has none yet
+    let bidder_token_account = get_associated_token_address(&bidder, &metadata.owner_nft_address);
+    if client.get_account(&bidder_token_account).is_err() {
+        instructions.push(create_associated_token_account(
+            &payer.pubkey(),
+            &bidder,
+            &metadata.owner_nft_address,
+            &spl_token::id(),
+        ));
+    }
+
+    instructions.push(accept_bid(
+        program_key,
+        metadata_key,
+        id,
+        bid.amount,
+        payer.pubkey(),
+        owner_token_account,
+        bidder,
+        bidder_token_account,
+        metadata
+            .creators
+            .iter()
+            .filter(|creator| creator.share > 0)
+            .map(|creator| creator.address)
+            .collect(),
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    (metadata, metadata_key)
+}
+
+fn start_auction_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (Auction, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let start_time = match app_matches.value_of("start_time") {
+        Some(start_time) => start_time.parse::<i64>().unwrap(),
+        None => unix_now(),
+    };
+    let duration = app_matches.value_of("duration").unwrap().parse::<i64>().unwrap();
+    let reserve_price = (app_matches.value_of("reserve_price").unwrap_or("0").parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+    let min_increment = (app_matches.value_of("min_increment").unwrap_or("0").parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    let auction_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes(), AUCTION.as_bytes()];
+    let (auction_key, _) = Pubkey::find_program_address(auction_seeds, &program_key);
+
+    let (owner, nft_owner_account) = find_nft_holder(&client, &metadata.owner_nft_address);
+    println!("--> holder {} - {}", owner, nft_owner_account);
+
+    let instructions = vec![start_auction(
+        program_key,
+        metadata_key,
+        id,
+        payer.pubkey(),
+        nft_owner_account,
+        metadata.owner_nft_address,
+        start_time,
+        start_time + duration,
+        reserve_price,
+        min_increment,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&auction_key).unwrap();
+    let auction: Auction = try_from_slice_unchecked(&account.data).unwrap();
+    (auction, auction_key)
+}
+
+fn place_auction_bid_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (Auction, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+    let amount = (app_matches.value_of("amount").unwrap().parse::<f64>().unwrap() * DEFAULT_LAMPORTS_PER_SOL as f64).round() as u64;
+
+    let auction_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes(), AUCTION.as_bytes()];
+    let (auction_key, _) = Pubkey::find_program_address(auction_seeds, &program_key);
+    let account = client.get_account(&auction_key).unwrap();
+    let auction: Auction = try_from_slice_unchecked(&account.data).unwrap();
+
+    // the program only looks at this account when there is a top bid to refund
+    let previous_top_bidder = if auction.has_bids() {
+        auction.top_bidder
+    } else {
+        payer.pubkey()
+    };
+
+    let instructions = vec![place_auction_bid(
+        program_key,
+        id,
+        amount,
+        payer.pubkey(),
+        previous_top_bidder,
+    )];
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&auction_key).unwrap();
+    let auction: Auction = try_from_slice_unchecked(&account.data).unwrap();
+    (auction, auction_key)
+}
+
+fn settle_auction_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    let auction_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes(), AUCTION.as_bytes()];
+    let (auction_key, _) = Pubkey::find_program_address(auction_seeds, &program_key);
+    let account = client.get_account(&auction_key).unwrap();
+    let auction: Auction = try_from_slice_unchecked(&account.data).unwrap();
+
+    let recipient = if auction.has_bids() {
+        auction.top_bidder
+    } else {
+        auction.seller
+    };
+    println!("---> Top bid of {} lamports, NFT goes to {}", auction.top_bid, recipient);
+
+    let mut instructions = vec![];
+
+    // whoever settles pays for the recipient token account when there is none yet
+    let recipient_token_account = get_associated_token_address(&recipient, &metadata.owner_nft_address);
+    if client.get_account(&recipient_token_account).is_err() {
+        instructions.push(create_associated_token_account(
+            &payer.pubkey(),
+            &recipient,
+            &metadata.owner_nft_address,
+            &spl_token::id(),
+        ));
+    }
+
+    // royalties are only paid out of a winning bid
+    let creators = if auction.has_bids() {
+        metadata
+            .creators
+            .iter()
+            .filter(|creator| creator.share > 0)
+            .map(|creator| creator.address)
+            .collect()
+    } else {
+        vec![]
+    };
+    instructions.push(settle_auction(
+        program_key,
+        metadata_key,
+        id,
+        auction.seller,
+        recipient_token_account,
+        creators,
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    (metadata, metadata_key)
+}
+
+fn clear_expired_listing_call(
+    app_matches: &ArgMatches,
+    payer: Keypair,
+    client: RpcClient,
+) -> (NFTData, Pubkey) {
+    let program_key = metaplex_token_metadata::id();
+    println!("--->Program_id: {}\n", program_key);
+
+    let id = app_matches.value_of("id").unwrap().parse::<u64>().unwrap();
+
+    let metadata_seeds = &[PREFIX.as_bytes(), &program_key.as_ref(), &id.to_le_bytes()];
+    let (metadata_key, _) = Pubkey::find_program_address(metadata_seeds, &program_key);
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    if metadata.sale_status != SaleStatus::Listed || !metadata.is_expired(unix_now()) {
+        println!("---> NFT {} has no expired listing", id);
+        return (metadata, metadata_key);
+    }
+
+    let mut instructions = vec![];
+
+    // whoever clears the listing pays for the seller token account when there is none yet
+    let seller_token_account = get_associated_token_address(&metadata.seller, &metadata.owner_nft_address);
+    if client.get_account(&seller_token_account).is_err() {
+        instructions.push(create_associated_token_account(
+            &payer.pubkey(),
+            &metadata.seller,
+            &metadata.owner_nft_address,
+            &spl_token::id(),
+        ));
+    }
+
+    instructions.push(clear_expired_listing(
+        program_key,
+        metadata_key,
+        id,
+        metadata.seller,
+        seller_token_account,
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let recent_blockhash = client.get_recent_blockhash().unwrap().0;
+    let signers = vec![&payer];
+    transaction.sign(&signers, recent_blockhash);
+    client.send_and_confirm_transaction(&transaction).unwrap();
+
+    let account = client.get_account(&metadata_key).unwrap();
+    let metadata: NFTData = try_from_slice_unchecked(&account.data).unwrap();
+    (metadata, metadata_key)
+}
+
+fn main() {
+    let app_matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg(
+            Arg::with_name("keypair")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .validator(is_valid_signer)
+                .takes_value(true)
+                .global(true)
+                .help("Filepath or URL to a keypair"),
+        )
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster [default: devnet]"),
+        )
+        .arg(
+            Arg::with_name("update_authority")
+                .long("update_authority")
+                .value_name("UPDATE_AUTHORITY")
+                .takes_value(true)
+                .global(true)
+                .help("Update authority filepath or url to keypair besides yourself, defaults to normal keypair"),
+        ).subcommand(
+            SubCommand::with_name("create_metadata_accounts")
+                .about("Create Metadata Accounts")
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .required(true)
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .help("Name for the NFT"),
                 ).arg(
+                    Arg::with_name("symbol")
+                        .long("symbol")
+                        .value_name("SYMBOL")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Symbol for the NFT, shown by wallets as the ticker"),
+                ).arg(
+                    Arg::with_name("listed_price")
+                        .long("price")
+                        .value_name("PRICE")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Published price for new sales (0-10000)"),
+                )
+                .arg(
+                    Arg::with_name("uri")
+                        .long("uri")
+                        .value_name("URI")
+                        .takes_value(true)
+                        .required(true)
+                        .help("URI for the NFT"),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .long("owner")
+                        .value_name("OWNER")
+                        .takes_value(true)
+                        .required_unless("mint_token")
+                        .help("Mint of the NFT, decimals 0 and supply 1"),
+                )
+                .arg(
+                    Arg::with_name("mint_token")
+                        .long("mint_token")
+                        .takes_value(false)
+                        .required(false)
+                        .conflicts_with("owner")
+                        .help("Create a new mint and mint its only token to yourself"),
+                )
+                .arg(
+                    Arg::with_name("seller_fee_basis_points")
+                        .long("seller_fee_basis_points")
+                        .value_name("SELLER_FEE_BASIS_POINTS")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Royalty paid to the creators on every sale, in basis points (0-10000)"),
+                )
+                .arg(
+                    Arg::with_name("creator")
+                        .long("creator")
+                        .value_name("ADDRESS:SHARE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(false)
+                        .help("Creator wallet and its percentage of the royalties, repeat for each creator"),
+                )
+                .arg(
+                    Arg::with_name("collection")
+                        .long("collection")
+                        .value_name("COLLECTION")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(false)
+                        .help("Collection account the NFT belongs to, verified separately by its authority"),
+                )
+                .arg(
+                    Arg::with_name("allow_owner_edits")
+                        .long("allow_owner_edits")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Let the owner of the NFT change its name and uri"),
+                )
+                .arg(
+                    Arg::with_name("immutable")
+                        .long("immutable")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Lock name, uri and creators so the update authority can never change them"),
+                )
+        ).subcommand(
+            SubCommand::with_name("update_metadata_accounts")
+                .about("Update Metadata Accounts")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id for update"),
+                )
+                .arg(
+                    Arg::with_name("listed_price")
+                        .long("price")
+                        .value_name("PRICE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Published price for new sales (0-10000)"),
+                )
+                .arg(
+                    Arg::with_name("expires_in")
+                        .long("expires_in")
+                        .value_name("EXPIRES_IN")
+                        .takes_value(true)
+                        .help("Seconds until the listing expires, never when left out"),
+                )
+        ).subcommand(
+            SubCommand::with_name("show")
+                .about("Show")
+                .arg(
+                    Arg::with_name("collection")
+                        .long("collection")
+                        .value_name("COLLECTION")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(false)
+                        .help("Only show verified members of this collection"),
+                )
+        ).subcommand(
+            SubCommand::with_name("buy_nft")
+                .about("Buy nft and mint NFT to your account")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id for update"),
+                )
+                .arg(
+                    Arg::with_name("listed_price")
+                        .long("new_price")
+                        .value_name("PRICE")
+                        .takes_value(true)
+                        .help("Published price for new sales (0-10000)"),
+                )
+                .arg(
+                    Arg::with_name("max_price")
+                        .long("max_price")
+                        .value_name("PRICE")
+                        .takes_value(true)
+                        .help("Most to pay for the NFT, defaults to the currently listed price"),
+                )
+        ).subcommand(
+            SubCommand::with_name("list_nft")
+                .about("Move the NFT into escrow and list it for sale")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to list"),
+                )
+                .arg(
                     Arg::with_name("listed_price")
                         .long("price")
                         .value_name("PRICE")
-                        .required(true)
                         .takes_value(true)
+                        .required(true)
                         .help("Published price for new sales (0-10000)"),
                 )
+                .arg(
+                    Arg::with_name("floor_price")
+                        .long("floor_price")
+                        .value_name("FLOOR_PRICE")
+                        .takes_value(true)
+                        .requires("duration")
+                        .help("Lowest price a declining price falls to, the price starts declining now"),
+                )
+                .arg(
+                    Arg::with_name("duration")
+                        .long("duration")
+                        .value_name("DURATION")
+                        .takes_value(true)
+                        .requires("floor_price")
+                        .help("Seconds the price takes to fall to the floor price"),
+                )
+                .arg(
+                    Arg::with_name("payment_mint")
+                        .long("payment_mint")
+                        .value_name("PAYMENT_MINT")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help("SPL mint the prices are in, in whole tokens, SOL when left out"),
+                )
+        ).subcommand(
+            SubCommand::with_name("delist_nft")
+                .about("Take the NFT back from escrow")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to delist"),
+                )
+        ).subcommand(
+            SubCommand::with_name("migrate_nft_id")
+                .about("Move an NFT account keyed by a single byte id to the u64 keyed layout")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to migrate (0-255)"),
+                )
+        ).subcommand(
+            SubCommand::with_name("sign_metadata")
+                .about("Verify yourself as a creator of the NFT")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to sign"),
+                )
+                .arg(
+                    Arg::with_name("remove")
+                        .long("remove")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Remove your verification instead"),
+                )
+        ).subcommand(
+            SubCommand::with_name("update_metadata")
+                .about("Change name, uri or creators as the update authority")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to update"),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(false)
+                        .help("New name for the NFT"),
+                )
                 .arg(
                     Arg::with_name("uri")
                         .long("uri")
                         .value_name("URI")
                         .takes_value(true)
+                        .required(false)
+                        .help("New URI for the NFT"),
+                )
+                .arg(
+                    Arg::with_name("creator")
+                        .long("creator")
+                        .value_name("ADDRESS:SHARE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(false)
+                        .help("Replace the creators, repeat for each creator"),
+                )
+                .arg(
+                    Arg::with_name("new_update_authority")
+                        .long("new_update_authority")
+                        .value_name("NEW_UPDATE_AUTHORITY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .required(false)
+                        .help("Hand the update authority over to this key"),
+                )
+                .arg(
+                    Arg::with_name("allow_owner_edits")
+                        .long("allow_owner_edits")
+                        .value_name("BOOL")
+                        .takes_value(true)
+                        .required(false)
+                        .help("Whether the owner may change name and uri (true or false)"),
+                )
+        ).subcommand(
+            SubCommand::with_name("edit_nft")
+                .about("Change name or uri as the owner, when the NFT allows it")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
                         .required(true)
-                        .help("URI for the NFT"),
+                        .takes_value(true)
+                        .help("NFT Id to edit"),
                 )
                 .arg(
-                    Arg::with_name("owner")
-                        .long("owner")
-                        .value_name("OWNER")
+                    Arg::with_name("name")
+                        .long("name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(false)
+                        .help("New name for the NFT"),
+                )
+                .arg(
+                    Arg::with_name("uri")
+                        .long("uri")
+                        .value_name("URI")
                         .takes_value(true)
+                        .required(false)
+                        .help("New URI for the NFT"),
+                )
+        ).subcommand(
+            SubCommand::with_name("create_collection")
+                .about("Create a collection with yourself as its authority")
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .value_name("NAME")
                         .required(true)
-                        .help("Pubkey for an owner NFT"),
+                        .takes_value(true)
+                        .help("Name for the collection"),
                 )
         ).subcommand(
-            SubCommand::with_name("update_metadata_accounts")
-                .about("Update Metadata Accounts")
+            SubCommand::with_name("verify_collection")
+                .about("Confirm an NFT belongs to your collection")
                 .arg(
                     Arg::with_name("id")
                         .long("id")
                         .value_name("ID")
                         .required(true)
                         .takes_value(true)
-                        .help("NFT Id for update"),
+                        .help("NFT Id to verify"),
                 )
                 .arg(
-                    Arg::with_name("listed_price")
-                        .long("price")
-                        .value_name("PRICE")
+                    Arg::with_name("unverify")
+                        .long("unverify")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Remove the verification instead"),
+                )
+        ).subcommand(
+            SubCommand::with_name("close_nft")
+                .about("Close an NFT account and reclaim its rent")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to close"),
+                )
+                .arg(
+                    Arg::with_name("recipient")
+                        .long("recipient")
+                        .value_name("RECIPIENT")
+                        .validator(is_valid_pubkey)
                         .takes_value(true)
+                        .required(false)
+                        .help("Receives the rent, defaults to yourself"),
+                )
+        ).subcommand(
+            SubCommand::with_name("burn_nft")
+                .about("Burn your NFT token and close its accounts")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
                         .required(true)
-                        .help("Published price for new sales (0-10000)"),
+                        .takes_value(true)
+                        .help("NFT Id to burn"),
                 )
         ).subcommand(
-            SubCommand::with_name("show")
-                .about("Show")
+            SubCommand::with_name("place_bid")
+                .about("Offer lamports for an NFT, held by the program until cancelled or accepted")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id to bid on"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Amount offered in SOL"),
+                )
         ).subcommand(
-            SubCommand::with_name("buy_nft")
-                .about("Buy nft and mint NFT to your account")
+            SubCommand::with_name("cancel_bid")
+                .about("Take back your bid on an NFT")
                 .arg(
                     Arg::with_name("id")
                         .long("id")
                         .value_name("ID")
                         .required(true)
                         .takes_value(true)
-                        .help("NFT Id for update"),
+                        .help("NFT Id the bid is on"),
                 )
+        ).subcommand(
+            SubCommand::with_name("accept_bid")
+                .about("Sell your NFT to a bidder")
                 .arg(
-                    Arg::with_name("name")
-                        .long("new_name")
-                        .value_name("NAME")
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
                         .takes_value(true)
-                        .help("Name for the NFT"),
-                ).arg(
-                    Arg::with_name("listed_price")
-                        .long("new_price")
-                        .value_name("PRICE")
+                        .help("NFT Id to sell"),
+                )
+                .arg(
+                    Arg::with_name("bidder")
+                        .long("bidder")
+                        .value_name("BIDDER")
+                        .validator(is_valid_pubkey)
+                        .required(true)
                         .takes_value(true)
-                        .help("Published price for new sales (0-10000)"),
+                        .help("Wallet of the bid to accept"),
                 )
+        ).subcommand(
+            SubCommand::with_name("start_auction")
+                .about("Put your NFT up for auction to the highest bidder")
                 .arg(
-                    Arg::with_name("uri")
-                        .long("new_uri")
-                        .value_name("URI")
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
                         .takes_value(true)
-                        .help("URI for the NFT"),
+                        .help("NFT Id to auction"),
+                )
+                .arg(
+                    Arg::with_name("start_time")
+                        .long("start_time")
+                        .value_name("START_TIME")
+                        .takes_value(true)
+                        .help("Unix timestamp from which bids are accepted, defaults to now"),
+                )
+                .arg(
+                    Arg::with_name("duration")
+                        .long("duration")
+                        .value_name("DURATION")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Seconds the auction runs for"),
+                )
+                .arg(
+                    Arg::with_name("reserve_price")
+                        .long("reserve_price")
+                        .value_name("RESERVE_PRICE")
+                        .takes_value(true)
+                        .help("Lowest first bid in SOL"),
+                )
+                .arg(
+                    Arg::with_name("min_increment")
+                        .long("min_increment")
+                        .value_name("MIN_INCREMENT")
+                        .takes_value(true)
+                        .help("SOL every bid must add on top of the top bid"),
+                )
+        ).subcommand(
+            SubCommand::with_name("place_auction_bid")
+                .about("Outbid the top bid of a running auction")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id being auctioned"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Amount offered in SOL"),
+                )
+        ).subcommand(
+            SubCommand::with_name("settle_auction")
+                .about("Hand an ended auction's NFT to the top bidder and pay the seller")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id which was auctioned"),
+                )
+        ).subcommand(
+            SubCommand::with_name("clear_expired_listing")
+                .about("Return the NFT of an expired listing to its seller")
+                .arg(
+                    Arg::with_name("id")
+                        .long("id")
+                        .value_name("ID")
+                        .required(true)
+                        .takes_value(true)
+                        .help("NFT Id of the expired listing"),
                 )
         //     SubCommand::with_name("puff_unpuffed_metadata")
                     // .about("Take metadata that still have variable length name, symbol, and uri fields and stretch them out with null symbols so they can be searched more easily by RPC.")
@@ -500,7 +1889,7 @@ fn main() {
             );
         }
         ("show", Some(arg_matches)) => {
-            get_all_nfts(&client);
+            get_all_nfts(&client, pubkey_of(arg_matches, "collection"));
         }
         ("buy_nft", Some(arg_matches)) => {
             let (metadata, metadata_key) = purchase_nft_call(arg_matches, payer, client);
@@ -509,6 +1898,121 @@ fn main() {
                 metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
             );
         }
+        ("list_nft", Some(arg_matches)) => {
+            let (metadata, metadata_key) = list_nft_call(arg_matches, payer, client);
+            println!(
+                "Listed metadata account with seller {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.seller, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("delist_nft", Some(arg_matches)) => {
+            let (metadata, metadata_key) = delist_nft_call(arg_matches, payer, client);
+            println!(
+                "Delisted metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("migrate_nft_id", Some(arg_matches)) => {
+            let (metadata, metadata_key) = migrate_nft_id_call(arg_matches, payer, client);
+            println!(
+                "Migrated metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("sign_metadata", Some(arg_matches)) => {
+            let (metadata, metadata_key) = sign_metadata_call(arg_matches, payer, client);
+            println!(
+                "Signed metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("update_metadata", Some(arg_matches)) => {
+            let (metadata, metadata_key) = update_metadata_call(arg_matches, payer, client);
+            println!(
+                "Update metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("edit_nft", Some(arg_matches)) => {
+            let (metadata, metadata_key) = edit_nft_call(arg_matches, payer, client);
+            println!(
+                "Edited metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("create_collection", Some(arg_matches)) => {
+            let (collection, collection_key) = create_collection_call(arg_matches, payer, client);
+            println!(
+                "Create collection account with authority {:?} and key {:?} and name of {:?}",
+                collection.authority, collection_key, collection.name
+            );
+        }
+        ("verify_collection", Some(arg_matches)) => {
+            let (metadata, metadata_key) = verify_collection_call(arg_matches, payer, client);
+            println!(
+                "Verified metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("close_nft", Some(arg_matches)) => {
+            let (metadata, metadata_key) = close_nft_call(arg_matches, payer, client);
+            println!(
+                "Closed metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("burn_nft", Some(arg_matches)) => {
+            let (metadata, metadata_key) = burn_nft_call(arg_matches, payer, client);
+            println!(
+                "Burned metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("place_bid", Some(arg_matches)) => {
+            let (bid, bid_key) = place_bid_call(arg_matches, payer, client);
+            println!(
+                "Placed bid account with bidder {:?} and key {:?} and amount of {} for id {}",
+                bid.bidder, bid_key, bid.amount, bid.id
+            );
+        }
+        ("cancel_bid", Some(arg_matches)) => {
+            cancel_bid_call(arg_matches, payer, client);
+        }
+        ("accept_bid", Some(arg_matches)) => {
+            let (metadata, metadata_key) = accept_bid_call(arg_matches, payer, client);
+            println!(
+                "Sold metadata account with owner {:?} and key {:?} and name of {:?} and id of {}",
+                metadata.owner_nft_address, metadata_key, metadata.name, metadata.id
+            );
+        }
+        ("start_auction", Some(arg_matches)) => {
+            let (auction, auction_key) = start_auction_call(arg_matches, payer, client);
+            println!(
+                "Started auction account with key {:?} for id {} ending at {} with reserve price of {}",
+                auction_key, auction.id, auction.end_time, auction.reserve_price
+            );
+        }
+        ("place_auction_bid", Some(arg_matches)) => {
+            let (auction, auction_key) = place_auction_bid_call(arg_matches, payer, client);
+            println!(
+                "Auction account with key {:?} has top bidder {:?} and top bid of {}",
+                auction_key, auction.top_bidder, auction.top_bid
+            );
+        }
+        ("settle_auction", Some(arg_matches)) => {
+            let (metadata, metadata_key) = settle_auction_call(arg_matches, payer, client);
+            println!(
+                "Settled metadata account with key {:?} and name of {:?} and last price of {}",
+                metadata_key, metadata.name, metadata.last_price
+            );
+        }
+        ("clear_expired_listing", Some(arg_matches)) => {
+            let (metadata, metadata_key) = clear_expired_listing_call(arg_matches, payer, client);
+            println!(
+                "Cleared metadata account with key {:?} and name of {:?} and status of {:?}",
+                metadata_key, metadata.name, metadata.sale_status
+            );
+        }
         _ => unreachable!(),
     }
 }
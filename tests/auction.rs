@@ -0,0 +1,275 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::{place_auction_bid, settle_auction, start_auction},
+        state::{Auction, SaleStatus, AUCTION, PREFIX},
+    },
+    solana_program::{borsh::try_from_slice_unchecked, pubkey::Pubkey},
+    solana_program_test::{tokio, ProgramTestContext},
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+const RESERVE_PRICE: u64 = 500_000_000;
+const MIN_INCREMENT: u64 = 100_000_000;
+const DURATION: i64 = 3600;
+
+fn auction_address(id: u64) -> Pubkey {
+    let program_id = metaplex_token_metadata::id();
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        AUCTION.as_bytes(),
+    ];
+    Pubkey::find_program_address(auction_seeds, &program_id).0
+}
+
+async fn get_auction(context: &mut ProgramTestContext, id: u64) -> Auction {
+    let account = context
+        .banks_client
+        .get_account(auction_address(id))
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_slice_unchecked(&account.data).unwrap()
+}
+
+/// NFT of `seller` put into an auction running for `DURATION` seconds from now
+async fn start(context: &mut ProgramTestContext, seller: &Keypair) -> TestNFT {
+    let nft = create_nft(context, &seller.pubkey(), 0).await;
+    let now = get_clock(context).await.unix_timestamp;
+    process(
+        context,
+        &[start_auction(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            seller.pubkey(),
+            nft.token_account,
+            nft.mint,
+            now,
+            now + DURATION,
+            RESERVE_PRICE,
+            MIN_INCREMENT,
+        )],
+        &[seller],
+    )
+    .await
+    .unwrap();
+    nft
+}
+
+async fn bid(
+    context: &mut ProgramTestContext,
+    nft: &TestNFT,
+    bidder: &Keypair,
+    amount: u64,
+    previous_top_bidder: Pubkey,
+) -> Result<(), solana_program_test::BanksClientError> {
+    process(
+        context,
+        &[place_auction_bid(
+            metaplex_token_metadata::id(),
+            nft.id,
+            amount,
+            bidder.pubkey(),
+            previous_top_bidder,
+        )],
+        &[bidder],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn outbid_refunds_previous_bidder() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let first = funded_wallet(&mut context).await;
+    let second = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+
+    let before = get_lamports(&mut context, &first.pubkey()).await;
+    bid(&mut context, &nft, &first, RESERVE_PRICE, Pubkey::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        get_lamports(&mut context, &first.pubkey()).await,
+        before - RESERVE_PRICE
+    );
+
+    let top_bid = RESERVE_PRICE + MIN_INCREMENT;
+    bid(&mut context, &nft, &second, top_bid, first.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(get_lamports(&mut context, &first.pubkey()).await, before);
+
+    let auction = get_auction(&mut context, nft.id).await;
+    assert_eq!(auction.top_bid, top_bid);
+    assert_eq!(auction.top_bidder, second.pubkey());
+}
+
+#[tokio::test]
+async fn bid_below_reserve_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let bidder = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+
+    let result = bid(
+        &mut context,
+        &nft,
+        &bidder,
+        RESERVE_PRICE - 1,
+        Pubkey::default(),
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::BidBelowReserve as u32);
+}
+
+#[tokio::test]
+async fn bid_under_the_increment_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let first = funded_wallet(&mut context).await;
+    let second = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+
+    bid(&mut context, &nft, &first, RESERVE_PRICE, Pubkey::default())
+        .await
+        .unwrap();
+    let result = bid(
+        &mut context,
+        &nft,
+        &second,
+        RESERVE_PRICE + MIN_INCREMENT - 1,
+        first.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::BidIncrementTooLow as u32
+    );
+}
+
+#[tokio::test]
+async fn settle_before_end_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+
+    let result = process(
+        &mut context,
+        &[settle_auction(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            seller.pubkey(),
+            nft.token_account,
+            vec![],
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::AuctionNotEnded as u32);
+}
+
+#[tokio::test]
+async fn settle_pays_seller_and_releases_nft_to_top_bidder() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let bidder = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+    bid(
+        &mut context,
+        &nft,
+        &bidder,
+        RESERVE_PRICE,
+        Pubkey::default(),
+    )
+    .await
+    .unwrap();
+    let bidder_token_account =
+        create_token_account(&mut context, &nft.mint, &bidder.pubkey()).await;
+
+    advance_clock(&mut context, DURATION).await;
+    let escrow = escrow_address(nft.id);
+    let rent = get_lamports(&mut context, &escrow).await
+        + get_lamports(&mut context, &auction_address(nft.id)).await
+        - RESERVE_PRICE;
+    let before = get_lamports(&mut context, &seller.pubkey()).await;
+    process(
+        &mut context,
+        &[settle_auction(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            seller.pubkey(),
+            bidder_token_account,
+            vec![],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_account(&mut context, &bidder_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert_eq!(
+        get_lamports(&mut context, &seller.pubkey()).await,
+        before + RESERVE_PRICE + rent
+    );
+    for closed in [escrow, auction_address(nft.id)] {
+        assert!(context
+            .banks_client
+            .get_account(closed)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert_eq!(metadata.sale_status, SaleStatus::NotForSale);
+    assert_eq!(metadata.seller, Pubkey::default());
+    assert_eq!(metadata.last_price, RESERVE_PRICE);
+}
+
+#[tokio::test]
+async fn settle_without_bids_returns_nft_to_seller() {
+    let mut context = program_test().start_with_context().await;
+    let seller = funded_wallet(&mut context).await;
+    let nft = start(&mut context, &seller).await;
+
+    advance_clock(&mut context, DURATION).await;
+    process(
+        &mut context,
+        &[settle_auction(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            seller.pubkey(),
+            nft.token_account,
+            vec![],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_account(&mut context, &nft.token_account)
+            .await
+            .amount,
+        1
+    );
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert_eq!(metadata.sale_status, SaleStatus::NotForSale);
+    assert_eq!(metadata.seller, Pubkey::default());
+    assert_eq!(metadata.last_price, 0);
+}
@@ -0,0 +1,268 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::{accept_bid, cancel_bid, list_nft, place_bid},
+        state::{Creator, BID, PREFIX},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{tokio, ProgramTestContext},
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+const AMOUNT: u64 = 1_000_000_000;
+
+fn bid_address(id: u64, bidder: &Pubkey) -> Pubkey {
+    let program_id = metaplex_token_metadata::id();
+    let bid_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        BID.as_bytes(),
+        bidder.as_ref(),
+    ];
+    Pubkey::find_program_address(bid_seeds, &program_id).0
+}
+
+async fn bid(context: &mut ProgramTestContext, nft: &TestNFT, bidder: &Keypair) {
+    process(
+        context,
+        &[place_bid(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            AMOUNT,
+            bidder.pubkey(),
+        )],
+        &[bidder],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn accept_while_held_pays_owner_and_creators() {
+    let mut context = program_test().start_with_context().await;
+    let owner = funded_wallet(&mut context).await;
+    let bidder = funded_wallet(&mut context).await;
+    let creators = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let args = NFTArgs {
+        seller_fee_basis_points: 1000,
+        creators: vec![
+            Creator {
+                address: creators[0],
+                verified: false,
+                share: 60,
+            },
+            Creator {
+                address: creators[1],
+                verified: false,
+                share: 40,
+            },
+        ],
+        ..NFTArgs::default()
+    };
+    let nft = create_nft_with(&mut context, &owner.pubkey(), args).await;
+    bid(&mut context, &nft, &bidder).await;
+    let bidder_token_account =
+        create_token_account(&mut context, &nft.mint, &bidder.pubkey()).await;
+
+    let owner_before = get_lamports(&mut context, &owner.pubkey()).await;
+    let bidder_before = get_lamports(&mut context, &bidder.pubkey()).await;
+    let bid_account = bid_address(nft.id, &bidder.pubkey());
+    let bid_rent = get_lamports(&mut context, &bid_account).await - AMOUNT;
+    process(
+        &mut context,
+        &[accept_bid(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            AMOUNT,
+            owner.pubkey(),
+            nft.token_account,
+            bidder.pubkey(),
+            bidder_token_account,
+            creators.to_vec(),
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    // 10% royalties split 60/40 between the creators, the rest to the owner
+    assert_eq!(
+        get_lamports(&mut context, &owner.pubkey()).await,
+        owner_before + 900_000_000
+    );
+    assert_eq!(get_lamports(&mut context, &creators[0]).await, 60_000_000);
+    assert_eq!(get_lamports(&mut context, &creators[1]).await, 40_000_000);
+    assert_eq!(
+        get_lamports(&mut context, &bidder.pubkey()).await,
+        bidder_before + bid_rent
+    );
+    assert!(context
+        .banks_client
+        .get_account(bid_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    assert_eq!(
+        get_token_account(&mut context, &nft.token_account)
+            .await
+            .amount,
+        0
+    );
+    assert_eq!(
+        get_token_account(&mut context, &bidder_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert_eq!(
+        get_metadata(&mut context, &nft.metadata).await.last_price,
+        AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn accept_while_listed_releases_escrow_and_ends_listing() {
+    let mut context = program_test().start_with_context().await;
+    let owner = funded_wallet(&mut context).await;
+    let bidder = funded_wallet(&mut context).await;
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+    process(
+        &mut context,
+        &[list_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            AMOUNT * 2,
+            None,
+            owner.pubkey(),
+            nft.token_account,
+            nft.mint,
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+    bid(&mut context, &nft, &bidder).await;
+    let bidder_token_account =
+        create_token_account(&mut context, &nft.mint, &bidder.pubkey()).await;
+
+    let escrow = escrow_address(nft.id);
+    let escrow_rent = get_lamports(&mut context, &escrow).await;
+    let owner_before = get_lamports(&mut context, &owner.pubkey()).await;
+    process(
+        &mut context,
+        &[accept_bid(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            AMOUNT,
+            owner.pubkey(),
+            nft.token_account,
+            bidder.pubkey(),
+            bidder_token_account,
+            vec![],
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_lamports(&mut context, &owner.pubkey()).await,
+        owner_before + AMOUNT + escrow_rent
+    );
+    assert!(context
+        .banks_client
+        .get_account(escrow)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_token_account(&mut context, &bidder_token_account)
+            .await
+            .amount,
+        1
+    );
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert!(!metadata.is_listed());
+    assert_eq!(metadata.seller, Pubkey::default());
+    assert_eq!(metadata.last_price, AMOUNT);
+}
+
+#[tokio::test]
+async fn cancel_refunds_the_bid() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let bidder = funded_wallet(&mut context).await;
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+
+    let before = get_lamports(&mut context, &bidder.pubkey()).await;
+    bid(&mut context, &nft, &bidder).await;
+    assert!(get_lamports(&mut context, &bidder.pubkey()).await < before - AMOUNT);
+
+    process(
+        &mut context,
+        &[cancel_bid(
+            metaplex_token_metadata::id(),
+            nft.id,
+            bidder.pubkey(),
+        )],
+        &[&bidder],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(get_lamports(&mut context, &bidder.pubkey()).await, before);
+    assert!(context
+        .banks_client
+        .get_account(bid_address(nft.id, &bidder.pubkey()))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn accept_of_another_amount_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let owner = funded_wallet(&mut context).await;
+    let bidder = funded_wallet(&mut context).await;
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+    bid(&mut context, &nft, &bidder).await;
+    let bidder_token_account =
+        create_token_account(&mut context, &nft.mint, &bidder.pubkey()).await;
+
+    // an amount other than the one the bidder escrowed
+    let result = process(
+        &mut context,
+        &[accept_bid(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            AMOUNT * 2,
+            owner.pubkey(),
+            nft.token_account,
+            bidder.pubkey(),
+            bidder_token_account,
+            vec![],
+        )],
+        &[&owner],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::BidAmountMismatch as u32
+    );
+    assert_eq!(
+        get_token_account(&mut context, &nft.token_account)
+            .await
+            .amount,
+        1
+    );
+}
@@ -0,0 +1,54 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::instruction::burn_nft,
+    solana_program::{program_option::COption, program_pack::Pack},
+    solana_program_test::tokio,
+    solana_sdk::signature::Signer,
+    spl_token::state::Mint,
+    utils::*,
+};
+
+#[tokio::test]
+async fn burn_removes_token_and_nft() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let nft = create_nft(&mut context, &owner, 0).await;
+
+    process(
+        &mut context,
+        &[burn_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            owner,
+            nft.token_account,
+            nft.mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(nft.token_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(context
+        .banks_client
+        .get_account(nft.metadata)
+        .await
+        .unwrap()
+        .is_none());
+    let mint = context
+        .banks_client
+        .get_account(nft.mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let mint = Mint::unpack(&mint.data).unwrap();
+    assert_eq!(mint.supply, 0);
+    assert_eq!(mint.mint_authority, COption::None);
+}
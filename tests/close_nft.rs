@@ -0,0 +1,124 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{error::MetadataError, instruction::close_nft},
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{tokio, ProgramTestContext},
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+async fn is_closed(context: &mut ProgramTestContext, address: &Pubkey) -> bool {
+    context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .is_none()
+}
+
+#[tokio::test]
+async fn owner_closes_nft_and_gets_the_rent() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+    let rent = get_lamports(&mut context, &nft.metadata).await;
+
+    process(
+        &mut context,
+        &[close_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            owner.pubkey(),
+            nft.token_account,
+            owner.pubkey(),
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    assert!(is_closed(&mut context, &nft.metadata).await);
+    assert_eq!(get_lamports(&mut context, &owner.pubkey()).await, rent);
+}
+
+#[tokio::test]
+async fn update_authority_closes_nft_without_holding_it() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+    // the payer created the NFT and is its update authority
+    let update_authority = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let rent = get_lamports(&mut context, &nft.metadata).await;
+
+    process(
+        &mut context,
+        &[close_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            update_authority,
+            Pubkey::new_unique(),
+            recipient,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert!(is_closed(&mut context, &nft.metadata).await);
+    assert_eq!(get_lamports(&mut context, &recipient).await, rent);
+}
+
+#[tokio::test]
+async fn third_party_cannot_close_nft() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let stranger = Keypair::new();
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+
+    let result = process(
+        &mut context,
+        &[close_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            stranger.pubkey(),
+            nft.token_account,
+            stranger.pubkey(),
+        )],
+        &[&stranger],
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::InvalidOwner as u32);
+    assert!(!is_closed(&mut context, &nft.metadata).await);
+}
+
+#[tokio::test]
+async fn closed_id_is_not_handed_out_again() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+
+    process(
+        &mut context,
+        &[close_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            owner.pubkey(),
+            nft.token_account,
+            owner.pubkey(),
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    let next = create_nft(&mut context, &owner.pubkey(), 0).await;
+    assert_eq!(next.id, nft.id + 1);
+    assert_ne!(next.metadata, nft.metadata);
+    assert!(is_closed(&mut context, &nft.metadata).await);
+}
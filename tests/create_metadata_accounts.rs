@@ -0,0 +1,40 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::error::MetadataError, solana_program_test::tokio,
+    solana_sdk::signature::Signer, utils::*,
+};
+
+#[tokio::test]
+async fn replayed_create_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let (mint, _) = create_nft_mint(&mut context, &owner).await;
+    let (instruction, metadata, _) =
+        create_nft_instruction(&mut context, &mint, NFTArgs::default()).await;
+    process(&mut context, std::slice::from_ref(&instruction), &[])
+        .await
+        .unwrap();
+    let created = context
+        .banks_client
+        .get_account(metadata)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // a fresh blockhash so the runtime does not drop the replay as a duplicate
+    context.warp_to_slot(100).unwrap();
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::AlreadyInitialized as u32
+    );
+
+    let after = context
+        .banks_client
+        .get_account(metadata)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after.data, created.data);
+}
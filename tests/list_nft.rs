@@ -0,0 +1,36 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::instruction::list_nft, solana_program::clock::Clock,
+    solana_program_test::tokio, solana_sdk::signature::Signer, utils::*,
+};
+
+#[tokio::test]
+async fn listing_records_when_it_was_listed() {
+    let mut context = program_test().start_with_context().await;
+    let seller = context.payer.pubkey();
+    let nft = create_nft(&mut context, &seller, 0).await;
+
+    process(
+        &mut context,
+        &[list_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            1_000_000,
+            None,
+            seller,
+            nft.token_account,
+            nft.mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert!(metadata.is_listed());
+    assert_eq!(metadata.listed_at, clock.unix_timestamp);
+    assert_eq!(metadata.expires_at, 0);
+}
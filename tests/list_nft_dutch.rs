@@ -0,0 +1,39 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{error::MetadataError, instruction::list_nft_dutch},
+    solana_program_test::tokio,
+    solana_sdk::signature::Signer,
+    utils::*,
+};
+
+#[tokio::test]
+async fn schedule_starting_at_zero_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let seller = context.payer.pubkey();
+    let nft = create_nft(&mut context, &seller, 0).await;
+
+    let result = process(
+        &mut context,
+        &[list_nft_dutch(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            1_000_000,
+            1_000,
+            0,
+            i64::MAX,
+            None,
+            seller,
+            nft.token_account,
+            nft.mint,
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::InvalidDutchSchedule as u32
+    );
+    assert!(!get_metadata(&mut context, &nft.metadata).await.is_listed());
+}
@@ -0,0 +1,87 @@
+mod utils;
+
+use {
+    borsh::BorshSerialize,
+    metaplex_token_metadata::{
+        instruction::migrate_nft_id,
+        state::{Key, LegacyNFTData, MAX_LEGACY_NFT_ID, MAX_NAME_LENGTH, MAX_URI_LENGTH, PREFIX},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::tokio,
+    solana_sdk::{account::Account, rent::Rent, signature::Signer},
+    utils::*,
+};
+
+fn puffed(value: &str, length: usize) -> String {
+    format!("{}{}", value, "\0".repeat(length - value.len()))
+}
+
+#[tokio::test]
+async fn migrate_after_create() {
+    let program_id = metaplex_token_metadata::id();
+    let legacy_id = 1u8;
+    let legacy_mint = Pubkey::new_unique();
+
+    let legacy = LegacyNFTData {
+        id: legacy_id,
+        name: puffed("Legacy NFT", MAX_NAME_LENGTH),
+        uri: puffed("https://example.com/legacy.json", MAX_URI_LENGTH),
+        last_price: 5,
+        listed_price: 7,
+        owner_nft_address: legacy_mint,
+    };
+    let data = legacy.try_to_vec().unwrap();
+    let legacy_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &[legacy_id]];
+    let (legacy_metadata, _) = Pubkey::find_program_address(legacy_seeds, &program_id);
+
+    let mut test = program_test();
+    test.add_account(
+        legacy_metadata,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context = test.start_with_context().await;
+
+    // a new NFT created before the migration must not take the legacy id
+    let owner = context.payer.pubkey();
+    let nft = create_nft(&mut context, &owner, 0).await;
+    assert_eq!(nft.id, MAX_LEGACY_NFT_ID + 1);
+
+    let id = legacy_id as u64;
+    let metadata_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()];
+    let (metadata, _) = Pubkey::find_program_address(metadata_seeds, &program_id);
+    let payer = context.payer.pubkey();
+    process(
+        &mut context,
+        &[migrate_nft_id(
+            program_id,
+            legacy_metadata,
+            metadata,
+            payer,
+            legacy_id,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let migrated = get_metadata(&mut context, &metadata).await;
+    assert_eq!(migrated.key, Key::NFTDataV1);
+    assert_eq!(migrated.id, id);
+    assert_eq!(migrated.name, legacy.name);
+    assert_eq!(migrated.uri, legacy.uri);
+    assert_eq!(migrated.last_price, 5);
+    assert_eq!(migrated.listed_price, 7);
+    assert_eq!(migrated.owner_nft_address, legacy_mint);
+    assert!(context
+        .banks_client
+        .get_account(legacy_metadata)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,94 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::{purchase_nft, update_nft_price},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+#[tokio::test]
+async fn create_without_payer_signature() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let (mint, _) = create_nft_mint(&mut context, &owner).await;
+    let (mut instruction, metadata, _) =
+        create_nft_instruction(&mut context, &mint, NFTArgs::default()).await;
+
+    // the payer is a different wallet which did not sign
+    let payer = Keypair::new().pubkey();
+    instruction.accounts[4].pubkey = payer;
+    instruction.accounts[4].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::AccountIsNotSigner as u32
+    );
+    assert!(context
+        .banks_client
+        .get_account(metadata)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn update_price_without_owner_signature() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let nft = create_nft(&mut context, &owner.pubkey(), 0).await;
+
+    let mut instruction = update_nft_price(
+        metaplex_token_metadata::id(),
+        nft.metadata,
+        nft.id,
+        10,
+        None,
+        owner.pubkey(),
+        nft.token_account,
+    );
+    instruction.accounts[1].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::AccountIsNotSigner as u32
+    );
+    assert_eq!(
+        get_metadata(&mut context, &nft.metadata).await.listed_price,
+        0
+    );
+}
+
+#[tokio::test]
+async fn purchase_without_payer_signature() {
+    let mut context = program_test().start_with_context().await;
+    let owner = context.payer.pubkey();
+    let nft = create_nft(&mut context, &owner, 0).await;
+    let buyer = Keypair::new();
+
+    let mut instruction = purchase_nft(
+        metaplex_token_metadata::id(),
+        nft.metadata,
+        nft.id,
+        None,
+        10,
+        buyer.pubkey(),
+        owner,
+        Pubkey::new_unique(),
+        None,
+        vec![],
+    );
+    instruction.accounts[1].is_signer = false;
+
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::AccountIsNotSigner as u32
+    );
+}
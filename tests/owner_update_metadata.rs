@@ -0,0 +1,78 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::owner_update_metadata,
+        state::{MAX_NAME_LENGTH, MAX_URI_LENGTH},
+    },
+    solana_program_test::tokio,
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+#[tokio::test]
+async fn owner_renames_mutable_nft() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let args = NFTArgs {
+        allow_owner_edits: true,
+        ..NFTArgs::default()
+    };
+    let nft = create_nft_with(&mut context, &owner.pubkey(), args).await;
+
+    process(
+        &mut context,
+        &[owner_update_metadata(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            owner.pubkey(),
+            nft.token_account,
+            Some("Renamed".to_owned()),
+            Some("https://example.com/renamed.json".to_owned()),
+        )],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert_eq!(metadata.name.trim_end_matches('\0'), "Renamed");
+    assert_eq!(
+        metadata.uri.trim_end_matches('\0'),
+        "https://example.com/renamed.json"
+    );
+    assert_eq!(metadata.name.len(), MAX_NAME_LENGTH);
+    assert_eq!(metadata.uri.len(), MAX_URI_LENGTH);
+}
+
+#[tokio::test]
+async fn owner_cannot_edit_immutable_nft() {
+    let mut context = program_test().start_with_context().await;
+    let owner = Keypair::new();
+    let args = NFTArgs {
+        is_mutable: false,
+        allow_owner_edits: true,
+        ..NFTArgs::default()
+    };
+    let nft = create_nft_with(&mut context, &owner.pubkey(), args).await;
+    let before = get_metadata(&mut context, &nft.metadata).await;
+
+    let result = process(
+        &mut context,
+        &[owner_update_metadata(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            owner.pubkey(),
+            nft.token_account,
+            Some("Renamed".to_owned()),
+            None,
+        )],
+        &[&owner],
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::DataIsImmutable as u32);
+    assert_eq!(get_metadata(&mut context, &nft.metadata).await, before);
+}
@@ -0,0 +1,255 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::{list_nft, purchase_nft, update_nft_price},
+        state::{Creator, ESCROW, PREFIX},
+    },
+    solana_program::{instruction::InstructionError, pubkey::Pubkey},
+    solana_program_test::tokio,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+    utils::*,
+};
+
+#[tokio::test]
+async fn price_raised_after_listing_is_rejected() {
+    let program_id = metaplex_token_metadata::id();
+    let mut context = program_test().start_with_context().await;
+    let seller = context.payer.pubkey();
+    let nft = create_nft(&mut context, &seller, 0).await;
+
+    let price = 1_000_000;
+    process(
+        &mut context,
+        &[list_nft(
+            program_id,
+            nft.metadata,
+            nft.id,
+            price,
+            None,
+            seller,
+            nft.token_account,
+            nft.mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let buyer = Keypair::new();
+    airdrop(&mut context, &buyer.pubkey(), 1_000_000_000).await;
+    let buyer_token_account = create_token_account(&mut context, &nft.mint, &buyer.pubkey()).await;
+
+    // the seller raises the price before the buyer's purchase lands
+    process(
+        &mut context,
+        &[update_nft_price(
+            program_id,
+            nft.metadata,
+            nft.id,
+            price * 2,
+            None,
+            seller,
+            nft.token_account,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &nft.id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    let (escrow, _) = Pubkey::find_program_address(escrow_seeds, &program_id);
+    let buyer_lamports = get_lamports(&mut context, &buyer.pubkey()).await;
+
+    let result = process(
+        &mut context,
+        &[purchase_nft(
+            program_id,
+            nft.metadata,
+            nft.id,
+            None,
+            price,
+            buyer.pubkey(),
+            seller,
+            buyer_token_account,
+            None,
+            vec![],
+        )],
+        &[&buyer],
+    )
+    .await;
+    assert_eq!(
+        custom_error(result),
+        MetadataError::PriceAboveMaximum as u32
+    );
+
+    assert_eq!(
+        get_lamports(&mut context, &buyer.pubkey()).await,
+        buyer_lamports
+    );
+    assert_eq!(get_token_account(&mut context, &escrow).await.amount, 1);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        0
+    );
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert!(metadata.is_listed());
+    assert_eq!(metadata.listed_price, price * 2);
+}
+
+#[tokio::test]
+async fn purchase_in_payment_mint_pays_seller_and_creators() {
+    let program_id = metaplex_token_metadata::id();
+    let mut context = program_test().start_with_context().await;
+    let seller = context.payer.pubkey();
+    let creators = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let args = NFTArgs {
+        seller_fee_basis_points: 1000,
+        creators: vec![
+            Creator {
+                address: creators[0],
+                verified: false,
+                share: 60,
+            },
+            Creator {
+                address: creators[1],
+                verified: false,
+                share: 40,
+            },
+        ],
+        ..NFTArgs::default()
+    };
+    let nft = create_nft_with(&mut context, &seller, args).await;
+
+    let payment_mint = create_mint(&mut context, 6).await;
+    let price = 1_000_000;
+    process(
+        &mut context,
+        &[list_nft(
+            program_id,
+            nft.metadata,
+            nft.id,
+            price,
+            Some(payment_mint),
+            seller,
+            nft.token_account,
+            nft.mint,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let buyer = Keypair::new();
+    let buyer_token_account = create_token_account(&mut context, &nft.mint, &buyer.pubkey()).await;
+    let buyer_payment_account =
+        create_token_account(&mut context, &payment_mint, &buyer.pubkey()).await;
+    mint_tokens(
+        &mut context,
+        &payment_mint,
+        &buyer_payment_account,
+        price * 2,
+    )
+    .await;
+    let seller_payment_account = create_token_account(&mut context, &payment_mint, &seller).await;
+    let mut creator_payment_accounts = vec![];
+    for creator in &creators {
+        creator_payment_accounts
+            .push(create_token_account(&mut context, &payment_mint, creator).await);
+    }
+
+    process(
+        &mut context,
+        &[purchase_nft(
+            program_id,
+            nft.metadata,
+            nft.id,
+            None,
+            price,
+            buyer.pubkey(),
+            seller,
+            buyer_token_account,
+            Some((buyer_payment_account, seller_payment_account)),
+            creator_payment_accounts.clone(),
+        )],
+        &[&buyer],
+    )
+    .await
+    .unwrap();
+
+    // 10% royalties split 60/40 between the creators, the rest to the seller
+    assert_eq!(
+        get_token_account(&mut context, &buyer_payment_account)
+            .await
+            .amount,
+        price
+    );
+    assert_eq!(
+        get_token_account(&mut context, &seller_payment_account)
+            .await
+            .amount,
+        900_000
+    );
+    assert_eq!(
+        get_token_account(&mut context, &creator_payment_accounts[0])
+            .await
+            .amount,
+        60_000
+    );
+    assert_eq!(
+        get_token_account(&mut context, &creator_payment_accounts[1])
+            .await
+            .amount,
+        40_000
+    );
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    let metadata = get_metadata(&mut context, &nft.metadata).await;
+    assert!(!metadata.is_listed());
+    assert_eq!(metadata.last_price, price);
+}
+
+#[tokio::test]
+async fn listing_needs_the_payment_mint_account() {
+    let program_id = metaplex_token_metadata::id();
+    let mut context = program_test().start_with_context().await;
+    let seller = context.payer.pubkey();
+    let nft = create_nft(&mut context, &seller, 0).await;
+
+    // a key which is not a mint
+    let mut instruction = list_nft(
+        program_id,
+        nft.metadata,
+        nft.id,
+        1_000_000,
+        Some(Pubkey::new_unique()),
+        seller,
+        nft.token_account,
+        nft.mint,
+    );
+    let result = process(&mut context, &[instruction.clone()], &[]).await;
+    assert_eq!(custom_error(result), MetadataError::IncorrectOwner as u32);
+
+    // the payment mint account left out
+    instruction.accounts.pop();
+    let result = process(&mut context, &[instruction], &[]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
@@ -0,0 +1,153 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::{list_nft, purchase_nft},
+        state::Creator,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{tokio, BanksClientError, ProgramTestContext},
+    solana_sdk::signature::{Keypair, Signer},
+    utils::*,
+};
+
+// 10% of the price rounds down to 99_999 lamports of royalties, split into 59_999.4 and 39_999.6
+const PRICE: u64 = 999_999;
+const SELLER_FEE_BASIS_POINTS: u16 = 1000;
+
+struct Sale {
+    nft: TestNFT,
+    seller: Keypair,
+    buyer: Keypair,
+    buyer_token_account: Pubkey,
+    /// Funded wallets with a 60 and a 40 share
+    creators: [Pubkey; 2],
+}
+
+async fn listed_nft(context: &mut ProgramTestContext) -> Sale {
+    let seller = funded_wallet(context).await;
+    let buyer = funded_wallet(context).await;
+    let creators = [
+        funded_wallet(context).await.pubkey(),
+        funded_wallet(context).await.pubkey(),
+    ];
+    let args = NFTArgs {
+        seller_fee_basis_points: SELLER_FEE_BASIS_POINTS,
+        creators: vec![
+            Creator {
+                address: creators[0],
+                verified: false,
+                share: 60,
+            },
+            Creator {
+                address: creators[1],
+                verified: false,
+                share: 40,
+            },
+        ],
+        ..NFTArgs::default()
+    };
+    let nft = create_nft_with(context, &seller.pubkey(), args).await;
+    process(
+        context,
+        &[list_nft(
+            metaplex_token_metadata::id(),
+            nft.metadata,
+            nft.id,
+            PRICE,
+            None,
+            seller.pubkey(),
+            nft.token_account,
+            nft.mint,
+        )],
+        &[&seller],
+    )
+    .await
+    .unwrap();
+    let buyer_token_account = create_token_account(context, &nft.mint, &buyer.pubkey()).await;
+    Sale {
+        nft,
+        seller,
+        buyer,
+        buyer_token_account,
+        creators,
+    }
+}
+
+async fn purchase(
+    context: &mut ProgramTestContext,
+    sale: &Sale,
+    creators: Vec<Pubkey>,
+) -> Result<(), BanksClientError> {
+    process(
+        context,
+        &[purchase_nft(
+            metaplex_token_metadata::id(),
+            sale.nft.metadata,
+            sale.nft.id,
+            None,
+            PRICE,
+            sale.buyer.pubkey(),
+            sale.seller.pubkey(),
+            sale.buyer_token_account,
+            None,
+            creators,
+        )],
+        &[&sale.buyer],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn purchase_splits_royalties_and_seller_keeps_the_remainder() {
+    let mut context = program_test().start_with_context().await;
+    let sale = listed_nft(&mut context).await;
+
+    let escrow_rent = get_lamports(&mut context, &escrow_address(sale.nft.id)).await;
+    let seller_before = get_lamports(&mut context, &sale.seller.pubkey()).await;
+    let creators_before = [
+        get_lamports(&mut context, &sale.creators[0]).await,
+        get_lamports(&mut context, &sale.creators[1]).await,
+    ];
+    purchase(&mut context, &sale, sale.creators.to_vec())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_lamports(&mut context, &sale.creators[0]).await,
+        creators_before[0] + 59_999
+    );
+    assert_eq!(
+        get_lamports(&mut context, &sale.creators[1]).await,
+        creators_before[1] + 39_999
+    );
+    // the lamport lost to rounding the shares stays with the seller
+    assert_eq!(
+        get_lamports(&mut context, &sale.seller.pubkey()).await,
+        seller_before + PRICE - 59_999 - 39_999 + escrow_rent
+    );
+}
+
+#[tokio::test]
+async fn purchase_without_every_creator_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let sale = listed_nft(&mut context).await;
+
+    let result = purchase(&mut context, &sale, vec![sale.creators[0]]).await;
+    assert_eq!(custom_error(result), MetadataError::CreatorNotFound as u32);
+}
+
+#[tokio::test]
+async fn purchase_with_creators_out_of_order_is_rejected() {
+    let mut context = program_test().start_with_context().await;
+    let sale = listed_nft(&mut context).await;
+
+    let result = purchase(
+        &mut context,
+        &sale,
+        vec![sale.creators[1], sale.creators[0]],
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::CreatorNotFound as u32);
+}
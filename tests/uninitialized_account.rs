@@ -0,0 +1,51 @@
+mod utils;
+
+use {
+    metaplex_token_metadata::{
+        error::MetadataError,
+        instruction::update_nft_price,
+        state::{MAX_DATA_SIZE, PREFIX},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::tokio,
+    solana_sdk::{account::Account, rent::Rent, signature::Signer},
+    utils::*,
+};
+
+#[tokio::test]
+async fn zeroed_nft_account_is_rejected() {
+    let program_id = metaplex_token_metadata::id();
+    let id = 300u64;
+    let metadata_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &id.to_le_bytes()];
+    let (metadata, _) = Pubkey::find_program_address(metadata_seeds, &program_id);
+
+    let mut test = program_test();
+    test.add_account(
+        metadata,
+        Account {
+            lamports: Rent::default().minimum_balance(MAX_DATA_SIZE),
+            data: vec![0; MAX_DATA_SIZE],
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context = test.start_with_context().await;
+
+    let owner = context.payer.pubkey();
+    let result = process(
+        &mut context,
+        &[update_nft_price(
+            program_id,
+            metadata,
+            id,
+            10,
+            None,
+            owner,
+            Pubkey::new_unique(),
+        )],
+        &[],
+    )
+    .await;
+    assert_eq!(custom_error(result), MetadataError::Uninitialized as u32);
+}
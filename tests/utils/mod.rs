@@ -0,0 +1,353 @@
+#![allow(dead_code)]
+
+use {
+    borsh::BorshDeserialize,
+    metaplex_token_metadata::{
+        instruction::{create_metadata_accounts, find_next_metadata_account},
+        processor,
+        state::{Config, Creator, NFTData, CONFIG, ESCROW, PREFIX},
+    },
+    solana_program::{
+        account_info::AccountInfo, borsh::try_from_slice_unchecked, clock::Clock,
+        entrypoint::ProgramResult, instruction::InstructionError, program_pack::Pack,
+        pubkey::Pubkey, system_instruction,
+    },
+    solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::Instruction,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::{Account, Mint},
+};
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    // native runs only: the processor ties the program id and the account slice to the
+    // lifetime of the account data, which the native loader only lends for this call. The
+    // leaked copies share that data through the same Rc<RefCell> handles, so every write still
+    // reaches the loader; only the few boxes holding the copies outlive the test.
+    let program_id = Box::leak(Box::new(*program_id));
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    processor::process_instruction(program_id, accounts, input)
+}
+
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "metaplex_token_metadata",
+        metaplex_token_metadata::id(),
+        processor!(process_instruction),
+    );
+    // `cargo test-bpf` loads the built program and the bundled token programs under the BPF
+    // loader, a plain `cargo test` runs the token programs natively next to this one
+    if cfg!(not(feature = "test-bpf")) {
+        program_test.add_program(
+            "spl_token",
+            spl_token::id(),
+            processor!(spl_token::processor::Processor::process),
+        );
+        program_test.add_program(
+            "spl_associated_token_account",
+            spl_associated_token_account::id(),
+            processor!(spl_associated_token_account::processor::process_instruction),
+        );
+    }
+    program_test
+}
+
+pub struct TestNFT {
+    pub id: u64,
+    pub metadata: Pubkey,
+    pub mint: Pubkey,
+    /// Token account of the owner holding the NFT
+    pub token_account: Pubkey,
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Custom program error the first instruction of a failed transaction returned
+pub fn custom_error(result: Result<(), BanksClientError>) -> u32 {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(code)) => code,
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+pub async fn get_metadata(context: &mut ProgramTestContext, metadata: &Pubkey) -> NFTData {
+    let account = context
+        .banks_client
+        .get_account(*metadata)
+        .await
+        .unwrap()
+        .unwrap();
+    try_from_slice_unchecked(&account.data).unwrap()
+}
+
+pub async fn get_token_account(
+    context: &mut ProgramTestContext,
+    token_account: &Pubkey,
+) -> Account {
+    let account = context
+        .banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    Account::unpack(&account.data).unwrap()
+}
+
+pub async fn get_clock(context: &mut ProgramTestContext) -> Clock {
+    context.banks_client.get_sysvar().await.unwrap()
+}
+
+/// Move the clock sysvar forward, the slot stays the same
+pub async fn advance_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let mut clock = get_clock(context).await;
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+/// Escrow token account holding the token of a listed or auctioned NFT
+pub fn escrow_address(id: u64) -> Pubkey {
+    let program_id = metaplex_token_metadata::id();
+    let escrow_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        &id.to_le_bytes(),
+        ESCROW.as_bytes(),
+    ];
+    Pubkey::find_program_address(escrow_seeds, &program_id).0
+}
+
+pub async fn get_lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub async fn airdrop(context: &mut ProgramTestContext, to: &Pubkey, lamports: u64) {
+    let payer = context.payer.pubkey();
+    process(
+        context,
+        &[system_instruction::transfer(&payer, to, lamports)],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+/// Wallet funded with 10 SOL
+pub async fn funded_wallet(context: &mut ProgramTestContext) -> Keypair {
+    let wallet = Keypair::new();
+    airdrop(context, &wallet.pubkey(), 10_000_000_000).await;
+    wallet
+}
+
+/// Empty token account of the mint owned by `owner`
+pub async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Pubkey {
+    let token_account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let payer = context.payer.pubkey();
+    process(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &token_account.pubkey(),
+                rent.minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&token_account],
+    )
+    .await
+    .unwrap();
+    token_account.pubkey()
+}
+
+/// Mint with the payer as mint authority
+pub async fn create_mint(context: &mut ProgramTestContext, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let payer = context.payer.pubkey();
+    process(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer,
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+    mint.pubkey()
+}
+
+/// Mint `amount` tokens of a mint created by `create_mint` into the token account
+pub async fn mint_tokens(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    amount: u64,
+) {
+    let payer = context.payer.pubkey();
+    process(
+        context,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            token_account,
+            &payer,
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+/// 1 of 1 mint with the payer as mint authority, its token held by `owner`
+pub async fn create_nft_mint(context: &mut ProgramTestContext, owner: &Pubkey) -> (Pubkey, Pubkey) {
+    let mint = create_mint(context, 0).await;
+    let token_account = create_token_account(context, &mint, owner).await;
+    mint_tokens(context, &mint, &token_account, 1).await;
+    (mint, token_account)
+}
+
+pub async fn get_config(context: &mut ProgramTestContext) -> Option<Config> {
+    let program_id = metaplex_token_metadata::id();
+    let config_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), CONFIG.as_bytes()];
+    let (config_key, _) = Pubkey::find_program_address(config_seeds, &program_id);
+    context
+        .banks_client
+        .get_account(config_key)
+        .await
+        .unwrap()
+        .map(|account| Config::try_from_slice(&account.data).unwrap())
+}
+
+/// Data of a test NFT, the defaults are a mutable NFT without creators or royalties
+pub struct NFTArgs {
+    pub listed_price: u64,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+    pub is_mutable: bool,
+    pub allow_owner_edits: bool,
+}
+
+impl Default for NFTArgs {
+    fn default() -> Self {
+        NFTArgs {
+            listed_price: 0,
+            seller_fee_basis_points: 0,
+            creators: vec![],
+            is_mutable: true,
+            allow_owner_edits: false,
+        }
+    }
+}
+
+/// Create instruction for the next id, the payer is the mint authority
+pub async fn create_nft_instruction(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    args: NFTArgs,
+) -> (Instruction, Pubkey, u64) {
+    let program_id = metaplex_token_metadata::id();
+    let config = get_config(context).await;
+    let (metadata, id) = find_next_metadata_account(&program_id, config.as_ref());
+    let payer = context.payer.pubkey();
+    let instruction = create_metadata_accounts(
+        program_id,
+        metadata,
+        *mint,
+        payer,
+        payer,
+        payer,
+        "Test NFT".to_owned(),
+        "TEST".to_owned(),
+        "https://example.com/nft.json".to_owned(),
+        0,
+        args.listed_price,
+        args.seller_fee_basis_points,
+        args.creators,
+        args.is_mutable,
+        args.allow_owner_edits,
+        None,
+        false,
+    );
+    (instruction, metadata, id)
+}
+
+/// NFT held by `owner`
+pub async fn create_nft(
+    context: &mut ProgramTestContext,
+    owner: &Pubkey,
+    listed_price: u64,
+) -> TestNFT {
+    let args = NFTArgs {
+        listed_price,
+        ..NFTArgs::default()
+    };
+    create_nft_with(context, owner, args).await
+}
+
+/// NFT held by `owner`, created with the given data
+pub async fn create_nft_with(
+    context: &mut ProgramTestContext,
+    owner: &Pubkey,
+    args: NFTArgs,
+) -> TestNFT {
+    let (mint, token_account) = create_nft_mint(context, owner).await;
+    let (instruction, metadata, id) = create_nft_instruction(context, &mint, args).await;
+    process(context, &[instruction], &[]).await.unwrap();
+    TestNFT {
+        id,
+        metadata,
+        mint,
+        token_account,
+    }
+}